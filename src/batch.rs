@@ -0,0 +1,224 @@
+// Solving the whole corpus at once. Problems are handed out to a pool of
+// worker threads, each problem solved in isolation so a panic only loses that
+// one, and solutions are only written when they beat what's already there.
+use core;
+use parse;
+use results::{Record,Results,results_path};
+
+use num::rational::BigRational;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::panic;
+use std::sync::{Arc,Mutex,mpsc};
+use std::thread;
+use std::time::{Duration,Instant};
+
+#[derive(Debug,Clone)]
+pub enum Outcome {
+	// first solution for the problem
+	Solved(f64),
+	// better than the existing solution, which had the first resemblance
+	Improved(f64, f64),
+	// no better than the existing solution, which is kept
+	Unchanged(f64),
+	// the existing solution is already perfect
+	Skipped,
+	Failed(String),
+	Panicked,
+}
+
+impl Outcome {
+	pub fn kind(&self) -> &'static str {
+		match *self {
+			Outcome::Solved(_) => "solved",
+			Outcome::Improved(..) => "improved",
+			Outcome::Unchanged(_) => "unchanged",
+			Outcome::Skipped => "skipped",
+			Outcome::Failed(_) => "failed",
+			Outcome::Panicked => "panicked",
+		}
+	}
+
+	// Whether the solution was written, replacing any there was
+	pub fn saved(&self) -> bool {
+		match *self {
+			Outcome::Solved(_) | Outcome::Improved(..) => true,
+			_ => false
+		}
+	}
+}
+
+impl fmt::Display for Outcome {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Outcome::Solved(r) => write!(f, "solved, resemblance {}", r),
+			Outcome::Improved(old, r) => write!(f, "improved from {} to {}", old, r),
+			Outcome::Unchanged(old) => write!(f, "no better than {}", old),
+			Outcome::Skipped => write!(f, "already perfect"),
+			Outcome::Failed(ref why) => write!(f, "failed: {}", why),
+			Outcome::Panicked => write!(f, "panicked"),
+		}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Report {
+	pub id: i64,
+	pub outcome: Outcome,
+	// the attempt, whether or not it was kept
+	pub record: Option<Record>,
+	pub elapsed: Duration,
+}
+
+fn solution_path(data_dir: &str, id: i64) -> String {
+	format!("{}/{:05}.solution.txt", data_dir, id)
+}
+
+// Every problem we have locally, in order
+pub fn problem_ids(data_dir: &str) -> io::Result<Vec<i64>> {
+	let mut ids = Vec::new();
+	for entry in try!(fs::read_dir(data_dir)) {
+		let name = try!(entry).file_name().to_string_lossy().into_owned();
+		if name.ends_with(".problem.txt") {
+			if let Ok(id) = name.split('.').next().unwrap().parse::<i64>() {
+				ids.push(id);
+			}
+		}
+	}
+	ids.sort();
+	Ok(ids)
+}
+
+// Resemblance of the solution we already have for the problem, if any
+pub fn existing_resemblance(data_dir: &str, id: i64, shape: &core::Shape<BigRational>) -> Option<f64> {
+	let file = match fs::File::open(solution_path(data_dir, id)) {
+		Ok(file) => file,
+		Err(_) => return None
+	};
+	parse::parse_solution::<BigRational, fs::File>(file).ok().map(|spec| core::resemblance(shape, &spec.dst_polys()))
+}
+
+// Writes the solution to a temporary file first, so a crash part way through
+// never leaves half a solution behind
+pub fn save_solution(data_dir: &str, id: i64, spec: &str) -> io::Result<()> {
+	let path = solution_path(data_dir, id);
+	let tmp = format!("{}.tmp", path);
+	{
+		let mut f = try!(fs::File::create(&tmp));
+		try!(f.write_all(spec.as_bytes()));
+		try!(f.sync_all());
+	}
+	fs::rename(&tmp, &path)
+}
+
+fn solve_one(data_dir: &str, id: i64, options: &core::SolveOptions) -> (Outcome, Option<Record>) {
+	let (shape, skeleton) = match ::load_problem(data_dir, id) {
+		Ok(problem) => problem,
+		Err(e) => return (Outcome::Failed(format!("{:?}", e)), None)
+	};
+	let existing = existing_resemblance(data_dir, id, &shape);
+	if existing == Some(1.0) {
+		return (Outcome::Skipped, None);
+	}
+	match core::solve(&shape, &skeleton, options) {
+		Ok(solution) => {
+			let record = Some(Record::new(id, &solution.strategy, &solution.spec, Some(solution.resemblance)));
+			let outcome = match existing {
+				Some(old) if solution.resemblance <= old => return (Outcome::Unchanged(old), record),
+				Some(old) => Outcome::Improved(old, solution.resemblance),
+				None => Outcome::Solved(solution.resemblance)
+			};
+			match save_solution(data_dir, id, &solution.spec) {
+				Ok(()) => (outcome, record),
+				Err(e) => (Outcome::Failed(format!("couldn't save: {}", e)), record)
+			}
+		},
+		Err(core::SolveError::Unsolved) => (Outcome::Failed("unsolved".to_string()), None),
+		Err(core::SolveError::Invalid(why)) => (Outcome::Failed(why), None)
+	}
+}
+
+// Solves every problem in ids on `workers` threads, returning a report for
+// each in the order they finished
+pub fn run_batch(data_dir: &str, ids: Vec<i64>, workers: usize, options: core::SolveOptions) -> Vec<Report> {
+	let total = ids.len();
+	let queue = Arc::new(Mutex::new(ids.into_iter().rev().collect::<Vec<i64>>()));
+	let options = Arc::new(options);
+	let (tx, rx) = mpsc::channel();
+	let handles: Vec<_> = (0..workers).map(|_| {
+		let (queue, options, tx, data_dir) = (queue.clone(), options.clone(), tx.clone(), data_dir.to_string());
+		thread::spawn(move || {
+			loop {
+				let next = queue.lock().unwrap().pop();
+				let id = match next {
+					Some(id) => id,
+					None => break
+				};
+				let start = Instant::now();
+				let (options, data_dir) = (options.clone(), data_dir.clone());
+				let (outcome, record) = match panic::catch_unwind(move || solve_one(&data_dir, id, &options)) {
+					Ok(result) => result,
+					Err(_) => (Outcome::Panicked, None)
+				};
+				if tx.send(Report{id: id, outcome: outcome, record: record, elapsed: start.elapsed()}).is_err() {
+					break;
+				}
+			}
+		})
+	}).collect();
+	drop(tx);
+
+	// attempts are recorded from here, so only one thread writes the results,
+	// each with how it turned out and whether it was kept
+	let mut reports = Vec::new();
+	for mut report in rx {
+		let strategy = report.record.as_ref().map_or("-", |r| r.strategy.as_str()).to_string();
+		println!("[{}/{}] Problem {}: {} by {} in {:.1}s", reports.len() + 1, total, report.id, report.outcome, strategy, seconds(&report.elapsed));
+		if let Some(ref mut record) = report.record {
+			record.outcome = Some(report.outcome.kind().to_string());
+			record.saved = report.outcome.saved();
+			if let Err(e) = Results::append(&results_path(data_dir), record) {
+				println!("Couldn't record the result: {}", e);
+			}
+		}
+		reports.push(report);
+	}
+	for handle in handles {
+		let _ = handle.join();
+	}
+	reports
+}
+
+fn seconds(d: &Duration) -> f64 {
+	d.as_secs() as f64 + d.subsec_nanos() as f64 / 1e9
+}
+
+pub fn print_summary(reports: &Vec<Report>, elapsed: Duration) {
+	println!("Batch of {} problems took {:.1}s", reports.len(), seconds(&elapsed));
+	for kind in ["solved", "improved", "unchanged", "skipped", "failed", "panicked"].iter() {
+		let ids: Vec<String> = reports.iter().filter(|r| r.outcome.kind() == *kind).map(|r| r.id.to_string()).collect();
+		// there are too many of the rest to list
+		if *kind == "failed" || *kind == "panicked" {
+			println!("  {:<10} {} {}", kind, ids.len(), ids.join(" "));
+		} else {
+			println!("  {:<10} {}", kind, ids.len());
+		}
+	}
+	let perfect = reports.iter().filter(|r| match r.outcome {
+		Outcome::Solved(x) | Outcome::Improved(_, x) => x == 1.0,
+		_ => false
+	}).count();
+	println!("  {} new perfect solutions", perfect);
+
+	let mut attempted: Vec<&Report> = reports.iter().filter(|r| r.outcome.kind() != "skipped").collect();
+	if !attempted.is_empty() {
+		let total: f64 = attempted.iter().map(|r| seconds(&r.elapsed)).sum();
+		println!("  mean time per problem: {:.1}s", total / attempted.len() as f64);
+		attempted.sort_by(|a, b| b.elapsed.cmp(&a.elapsed));
+		let slowest: Vec<String> = attempted.iter().take(5).map(|r| format!("{} ({:.1}s)", r.id, seconds(&r.elapsed))).collect();
+		println!("  slowest: {}", slowest.join(", "));
+	}
+}
@@ -0,0 +1,116 @@
+// Blobs from the server, kept in the blobs directory under their SHA-1, which
+// is the hash the server knows them by. Every read checks the hash again, so
+// a truncated download or an error page saved in its place is noticed and
+// thrown away rather than used.
+use std::fs;
+use std::io;
+use std::io::{Read,Write};
+use std::path::Path;
+
+use sha1::Sha1;
+
+// SHA-1 as the server writes it, for blobs and the specs we hash ourselves
+pub fn hash(data: &[u8]) -> String {
+	let mut sha = Sha1::new();
+	sha.update(data);
+	sha.digest().to_string()
+}
+
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+	let mut data = Vec::new();
+	try!(try!(fs::File::open(path)).read_to_end(&mut data));
+	Ok(data)
+}
+
+// Whether the file at path is there and has the given hash
+pub fn verify(path: &str, expected: &str) -> bool {
+	read(path).map(|data| hash(&data) == expected).unwrap_or(false)
+}
+
+#[derive(Debug,Clone)]
+pub struct BlobCache {
+	pub dir: String,
+}
+
+impl BlobCache {
+	pub fn new(data_dir: &str) -> BlobCache {
+		BlobCache{dir: format!("{}/blobs", data_dir)}
+	}
+
+	pub fn path(&self, hash: &str) -> String {
+		format!("{}/{}", self.dir, hash)
+	}
+
+	// The blob if we have a good copy. A bad copy is deleted so that it gets
+	// fetched again.
+	pub fn get(&self, expected: &str) -> io::Result<Option<Vec<u8>>> {
+		let path = self.path(expected);
+		let data = match read(&path) {
+			Ok(data) => data,
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e)
+		};
+		if hash(&data) != expected {
+			println!("Blob {} is corrupt, dropping it", expected);
+			try!(fs::remove_file(&path));
+			return Ok(None);
+		}
+		Ok(Some(data))
+	}
+
+	// Stores a blob, provided it really is the one asked for
+	pub fn put(&self, expected: &str, data: &[u8]) -> io::Result<()> {
+		let actual = hash(data);
+		if actual != expected {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, format!("blob {} has hash {}", expected, actual)));
+		}
+		try!(fs::create_dir_all(&self.dir));
+		let path = self.path(expected);
+		let tmp = format!("{}.tmp", path);
+		{
+			let mut f = try!(fs::File::create(&tmp));
+			try!(f.write_all(data));
+			try!(f.sync_all());
+		}
+		fs::rename(&tmp, &path)
+	}
+
+	// Puts a cached blob at path too, as a hard link where the filesystem
+	// allows and a copy otherwise
+	pub fn link(&self, hash: &str, path: &str) -> io::Result<()> {
+		if Path::new(path).exists() {
+			try!(fs::remove_file(path));
+		}
+		fs::hard_link(self.path(hash), path).or_else(|_| fs::copy(self.path(hash), path).map(|_| ()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	use std::fs;
+	use std::io::Write;
+
+	#[test]
+	fn test_blob_cache() {
+		let dir = env::temp_dir().join(format!("superlegit-blobs-{}", ::std::process::id())).to_string_lossy().into_owned();
+		let cache = BlobCache::new(&dir);
+		let blob = b"1\n4\n0,0\n1,0\n1,1\n0,1\n";
+		let h = hash(blob);
+		assert_eq!(None, cache.get(&h).unwrap());
+		assert!(cache.put(&h, b"<html>Error 502</html>").is_err());
+		cache.put(&h, blob).unwrap();
+		assert_eq!(Some(blob.to_vec()), cache.get(&h).unwrap());
+
+		let problem = format!("{}/00001.problem.txt", dir);
+		cache.link(&h, &problem).unwrap();
+		assert!(verify(&problem, &h));
+
+		// a damaged entry goes, and won't be handed out
+		fs::File::create(cache.path(&h)).unwrap().write_all(b"1\n4\n0,0\n").unwrap();
+		assert_eq!(None, cache.get(&h).unwrap());
+		assert!(!Path::new(&cache.path(&h)).exists());
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
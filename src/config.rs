@@ -0,0 +1,203 @@
+// Settings that differ between team members and machines. The defaults are
+// overridden by superlegit.json (or whichever file SUPERLEGIT_CONFIG names),
+// and that in turn by SUPERLEGIT_<KEY> environment variables, eg.
+// SUPERLEGIT_API_KEY or SUPERLEGIT_DATA_DIR.
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::time::Duration;
+
+use rustc_serialize::json::Json;
+
+use core::{BeamOptions,SolveOptions};
+use BASEPATH;
+
+pub const CONFIG_FILE: &'static str = "superlegit.json";
+
+const KEYS: [&'static str; 12] = [
+	"api_key", "username", "base_url", "data_dir", "request_interval_ms", "requests_per_hour",
+	"time_limit", "workers", "strategies", "unfold_steps", "beam_depth", "beam_width"
+];
+
+#[derive(Debug)]
+pub enum ConfigError {
+	Io(io::Error),
+	Parse(String),
+	// a key we don't know, or a value that doesn't suit it
+	Bad(String, String),
+}
+
+impl From<io::Error> for ConfigError {
+	fn from(e: io::Error) -> ConfigError {
+		ConfigError::Io(e)
+	}
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ConfigError::Io(ref e) => write!(f, "{}", e),
+			ConfigError::Parse(ref why) => write!(f, "Can't parse the config: {}", why),
+			ConfigError::Bad(ref key, ref value) => write!(f, "Bad config setting {} = {:?}", key, value),
+		}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Config {
+	pub api_key: String,
+	// our team's username, which tells our own problems apart
+	pub username: String,
+	// everything in the API lives under here
+	pub base_url: String,
+	// problems, solutions and results
+	pub data_dir: String,
+	// the server allows one request a second, and only so many an hour
+	pub request_interval: Duration,
+	pub requests_per_hour: usize,
+	// solver defaults, which the command line can override
+	pub time_limit: Option<Duration>,
+	pub workers: usize,
+	pub strategies: Vec<String>,
+	pub unfold_steps: usize,
+	pub beam: BeamOptions,
+}
+
+impl Default for Config {
+	fn default() -> Config {
+		let solve = SolveOptions::default();
+		Config{
+			api_key: String::new(),
+			username: String::new(),
+			base_url: "http://2016sv.icfpcontest.org/api".to_string(),
+			data_dir: BASEPATH.to_string(),
+			request_interval: Duration::from_millis(1000),
+			requests_per_hour: 1000,
+			time_limit: solve.time_limit,
+			workers: 4,
+			strategies: solve.strategies,
+			unfold_steps: solve.unfold_steps,
+			beam: solve.beam,
+		}
+	}
+}
+
+fn number<T: ::std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+	value.trim().parse::<T>().map_err(|_| ConfigError::Bad(key.to_string(), value.to_string()))
+}
+
+impl Config {
+	// The defaults, then the config file if there is one, then the environment
+	pub fn load() -> Result<Config, ConfigError> {
+		let mut config = Config::default();
+		let path = env::var("SUPERLEGIT_CONFIG").unwrap_or(CONFIG_FILE.to_string());
+		match fs::File::open(&path) {
+			Ok(mut file) => {
+				let mut data = String::new();
+				try!(file.read_to_string(&mut data));
+				try!(config.apply_json(&data));
+			},
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => {},
+			Err(e) => return Err(ConfigError::Io(e))
+		}
+		try!(config.apply_env(|name| env::var(name).ok()));
+		Ok(config)
+	}
+
+	pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+		match key {
+			"api_key" => self.api_key = value.trim().to_string(),
+			"username" => self.username = value.trim().to_string(),
+			"base_url" => self.base_url = value.trim().trim_end_matches('/').to_string(),
+			"data_dir" => self.data_dir = value.trim().to_string(),
+			"request_interval_ms" => self.request_interval = Duration::from_millis(try!(number(key, value))),
+			// 0 for no hourly cap
+			"requests_per_hour" => self.requests_per_hour = try!(number(key, value)),
+			// in seconds, with 0 meaning no limit
+			"time_limit" => {
+				let seconds: u64 = try!(number(key, value));
+				self.time_limit = if seconds == 0 { None } else { Some(Duration::from_secs(seconds)) };
+			},
+			"workers" => self.workers = try!(number(key, value)),
+			// comma separated, or empty for all of them
+			"strategies" => self.strategies = value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+			"unfold_steps" => self.unfold_steps = try!(number(key, value)),
+			"beam_depth" => self.beam.depth = try!(number(key, value)),
+			"beam_width" => self.beam.width = try!(number(key, value)),
+			_ => return Err(ConfigError::Bad(key.to_string(), value.to_string()))
+		}
+		Ok(())
+	}
+
+	// Settings from a JSON object, eg. {"api_key": "...", "workers": 8}
+	pub fn apply_json(&mut self, data: &str) -> Result<(), ConfigError> {
+		let json = try!(Json::from_str(data).map_err(|e| ConfigError::Parse(format!("{}", e))));
+		let obj = match json.as_object() {
+			Some(obj) => obj,
+			None => return Err(ConfigError::Parse("expected an object".to_string()))
+		};
+		for (key, value) in obj.iter() {
+			let value = match *value {
+				Json::String(ref s) => s.clone(),
+				Json::Array(ref items) => items.iter().map(|i| i.as_string().map_or(i.to_string(), |s| s.to_string())).collect::<Vec<String>>().join(","),
+				ref other => other.to_string()
+			};
+			try!(self.set(key, &value));
+		}
+		Ok(())
+	}
+
+	// Settings from SUPERLEGIT_<KEY> variables, looked up with `var`
+	pub fn apply_env<F: Fn(&str) -> Option<String>>(&mut self, var: F) -> Result<(), ConfigError> {
+		for key in KEYS.iter() {
+			if let Some(value) = var(&format!("SUPERLEGIT_{}", key.to_uppercase())) {
+				try!(self.set(key, &value));
+			}
+		}
+		Ok(())
+	}
+
+	pub fn solve_options(&self) -> SolveOptions {
+		SolveOptions{
+			strategies: self.strategies.clone(),
+			unfold_steps: self.unfold_steps,
+			beam: self.beam.clone(),
+			time_limit: self.time_limit,
+			..SolveOptions::default()
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_config() {
+		let mut config = Config::default();
+		config.apply_json(r#"{"api_key": "60-abc", "data_dir": "/tmp/problems", "workers": 8, "strategies": ["hull", "beam"], "time_limit": 0}"#).unwrap();
+		assert_eq!("60-abc", config.api_key);
+		assert_eq!("/tmp/problems", config.data_dir);
+		assert_eq!(8, config.workers);
+		assert_eq!(vec!["hull", "beam"], config.strategies);
+		assert_eq!(None, config.time_limit);
+
+		// the environment wins
+		config.apply_env(|name| match name {
+			"SUPERLEGIT_API_KEY" => Some("61-def".to_string()),
+			"SUPERLEGIT_BASE_URL" => Some("http://127.0.0.1:8000/api/".to_string()),
+			"SUPERLEGIT_TIME_LIMIT" => Some("30".to_string()),
+			_ => None
+		}).unwrap();
+		assert_eq!("61-def", config.api_key);
+		assert_eq!("http://127.0.0.1:8000/api", config.base_url);
+		assert_eq!(Some(Duration::from_secs(30)), config.solve_options().time_limit);
+		assert_eq!(8, config.workers);
+
+		assert!(config.apply_json(r#"{"workers": "lots"}"#).is_err());
+		assert!(config.apply_json(r#"{"colour": "blue"}"#).is_err());
+		assert!(config.apply_json("[1, 2]").is_err());
+	}
+}
@@ -0,0 +1,194 @@
+// Planar arrangements of line segments, ie. turning a skeleton into facets
+use super::*;
+
+use std::cmp::{max,min,Ordering};
+use std::collections::{BTreeMap,BTreeSet};
+
+// Points at which segment b touches segment a (a crossing, or the ends of b
+// when the two are collinear and overlap).
+fn touching<N: Num>(a: &Line<N>, b: &Line<N>) -> Vec<Point<N>> {
+	let (r, s) = (&a.p2 - &a.p1, &b.p2 - &b.p1);
+	let qp = &b.p1 - &a.p1;
+	let denom = cross_scalar(&r, &s);
+	if denom != N::zero() {
+		let t = cross_scalar(&qp, &s) / denom.clone();
+		let u = cross_scalar(&qp, &r) / denom;
+		if t >= N::zero() && t <= N::one() && u >= N::zero() && u <= N::one() {
+			return vec![&a.p1 + r.scale(t)];
+		}
+		return Vec::new();
+	}
+	if cross_scalar(&qp, &r) != N::zero() {
+		return Vec::new();
+	}
+	let len2 = r.dot(r.clone());
+	vec![b.p1.clone(), b.p2.clone()].into_iter().filter(|p| {
+		let t = (p - &a.p1).dot(r.clone());
+		t >= N::zero() && t <= len2
+	}).collect()
+}
+
+// Splits every segment wherever it touches another. Returns the distinct
+// vertices and the (undirected, deduplicated) edges between them.
+pub fn planar_graph<N: Num>(lines: &Vec<Line<N>>) -> (Vec<Point<N>>, Vec<(usize, usize)>) {
+	let mut index = BTreeMap::new();
+	let mut points = Vec::new();
+	let mut edges = BTreeSet::new();
+	for (i, a) in lines.iter().enumerate() {
+		let d = &a.p2 - &a.p1;
+		let mut cuts = vec![(N::zero(), a.p1.clone()), (d.dot(d.clone()), a.p2.clone())];
+		for (j, b) in lines.iter().enumerate() {
+			if i != j {
+				for p in touching(a, b) {
+					cuts.push(((&p - &a.p1).dot(d.clone()), p));
+				}
+			}
+		}
+		cuts.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+		let mut prev = None;
+		for (_, p) in cuts {
+			let next = points.len();
+			let id = *index.entry(p.clone()).or_insert(next);
+			if id == next {
+				points.push(p);
+			}
+			if let Some(q) = prev {
+				if q != id {
+					edges.insert((min(q, id), max(q, id)));
+				}
+			}
+			prev = Some(id);
+		}
+	}
+	(points, edges.into_iter().collect())
+}
+
+// Orders directions counter-clockwise by angle, as atan2 would but exactly:
+// first the half turn from just past straight left, then which is further round
+pub fn by_angle<N: Num>(a: &Point<N>, b: &Point<N>) -> Ordering {
+	let lower = |d: &Point<N>| d.y < N::zero() || (d.y == N::zero() && d.x > N::zero());
+	match (lower(a), lower(b)) {
+		(true, false) => Ordering::Less,
+		(false, true) => Ordering::Greater,
+		_ => {
+			let turn = cross_scalar(a, b);
+			if turn > N::zero() { Ordering::Less } else if turn < N::zero() { Ordering::Greater } else { Ordering::Equal }
+		}
+	}
+}
+
+// Which of the ways on is immediately clockwise of the way back, going round
+// past it if need be, and only turning back at a dead end. This is how faces
+// are walked.
+pub fn clockwise_of<N: Num>(back: &Point<N>, ways: &[Point<N>]) -> Option<usize> {
+	let pick = |order: Ordering| (0..ways.len()).filter(|&i| by_angle(&ways[i], back) == order).max_by(|&i, &j| by_angle(&ways[i], &ways[j]));
+	pick(Ordering::Less).or_else(|| pick(Ordering::Greater)).or_else(|| pick(Ordering::Equal))
+}
+
+// The bounded faces of the arrangement of `lines`, each counter-clockwise.
+// Vertices where other segments meet a face's boundary are kept, so adjacent
+// faces always share whole edges.
+pub fn faces<N: Num>(lines: &Vec<Line<N>>) -> Vec<Polygon<N>> {
+	let (points, edges) = planar_graph(lines);
+	let mut around: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+	for &(a, b) in edges.iter() {
+		around[a].push(b);
+		around[b].push(a);
+	}
+	let ways: Vec<Vec<Point<N>>> = around.iter().enumerate().map(|(v, nbrs)| nbrs.iter().map(|&w| &points[w] - &points[v]).collect()).collect();
+
+	// walk each half edge keeping the face on the left, ie. at each vertex take
+	// the edge immediately clockwise of the one we arrived along
+	let mut visited = BTreeSet::new();
+	let mut faces = Vec::new();
+	for &(a, b) in edges.iter() {
+		for &(u0, v0) in [(a, b), (b, a)].iter() {
+			let (mut u, mut v) = (u0, v0);
+			let mut face = Vec::new();
+			while visited.insert((u, v)) {
+				face.push(points[u].clone());
+				let back = &points[u] - &points[v];
+				let w = around[v][clockwise_of(&back, &ways[v]).unwrap()];
+				u = v;
+				v = w;
+			}
+			if face.len() >= 3 {
+				let poly = Polygon::new(face);
+				// the unbounded face comes out clockwise
+				if poly.signed_area() > N::zero() {
+					faces.push(poly);
+				}
+			}
+		}
+	}
+	faces
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[test]
+	fn test_planar_graph() {
+		// a plus sign splits into four edges around a centre vertex
+		let lines = vec![Line::new(p(0.0, 1.0), p(2.0, 1.0)), Line::new(p(1.0, 0.0), p(1.0, 2.0))];
+		let (points, edges) = planar_graph(&lines);
+		assert_eq!(5, points.len());
+		assert_eq!(4, edges.len());
+
+		// overlapping collinear segments are merged
+		let lines = vec![Line::new(p(0.0, 0.0), p(2.0, 0.0)), Line::new(p(1.0, 0.0), p(3.0, 0.0))];
+		let (points, edges) = planar_graph(&lines);
+		assert_eq!(4, points.len());
+		assert_eq!(3, edges.len());
+	}
+
+	#[test]
+	fn test_faces() {
+		// unit square cut along both diagonals makes four triangles
+		let lines = vec![
+			Line::new(p(0.0, 0.0), p(1.0, 0.0)), Line::new(p(1.0, 0.0), p(1.0, 1.0)),
+			Line::new(p(1.0, 1.0), p(0.0, 1.0)), Line::new(p(0.0, 1.0), p(0.0, 0.0)),
+			Line::new(p(0.0, 0.0), p(1.0, 1.0)), Line::new(p(1.0, 0.0), p(0.0, 1.0)),
+		];
+		let triangles = faces(&lines);
+		assert_eq!(4, triangles.len());
+		for face in triangles {
+			assert_eq!(3, face.points.len());
+			assert_eq!(0.25, face.signed_area());
+		}
+
+		// a T junction leaves a collinear vertex on the bigger face
+		let lines = vec![
+			Line::new(p(0.0, 0.0), p(2.0, 0.0)), Line::new(p(2.0, 0.0), p(2.0, 1.0)),
+			Line::new(p(2.0, 1.0), p(0.0, 1.0)), Line::new(p(0.0, 1.0), p(0.0, 0.0)),
+			Line::new(p(1.0, 0.0), p(1.0, 1.0)), Line::new(p(1.0, 0.5), p(2.0, 0.5)),
+		];
+		let mut sizes: Vec<usize> = faces(&lines).iter().map(|f| f.points.len()).collect();
+		sizes.sort();
+		assert_eq!(vec![4, 4, 5], sizes);
+	}
+
+	#[test]
+	fn test_faces_near_parallel() {
+		// a fan of thin triangles pointing left from the origin, their edges
+		// too close to parallel for atan2 to tell apart
+		let tiny = rat(1, 1000000000000) * rat(1, 1000000000000);
+		let origin = p(rat(0, 1), rat(0, 1));
+		let ends: Vec<Point<BigRational>> = (0..4).map(|k| p(rat(-1, 1), tiny.clone() * rat(k, 1))).collect();
+		let mut lines: Vec<Line<BigRational>> = ends.iter().map(|end| Line::new(origin.clone(), end.clone())).collect();
+		lines.push(Line::new(ends[0].clone(), ends[3].clone()));
+		let triangles = faces(&lines);
+		assert_eq!(3, triangles.len());
+		for face in triangles {
+			assert_eq!(3, face.points.len());
+			assert_eq!(tiny.clone() * rat(1, 2), face.signed_area());
+		}
+
+		// the way just clockwise of straight back, and round past it
+		let ways = vec![ends[1].clone(), ends[3].clone(), p(rat(0, 1), rat(1, 1))];
+		assert_eq!(Some(1), clockwise_of(&p(rat(-1, 1), tiny.clone() * rat(2, 1)), &ways));
+		assert_eq!(Some(0), clockwise_of(&p(rat(-1, 1), rat(0, 1)), &ways));
+	}
+}
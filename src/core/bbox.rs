@@ -0,0 +1,192 @@
+// Fallback for anything: fold the paper down to the silhouette's bounding
+// rectangle, either axis aligned or turned to line up with a hull edge.
+use super::*;
+use super::super::matrix::Matrix33;
+
+use num::BigInt;
+use num::rational::BigRational;
+
+use std::cmp::Ordering;
+
+const SCORING_SAMPLES: usize = 48;
+// folds allowed along each side before we give up on getting any narrower
+const MAX_FOLDS: usize = 16;
+
+// A rectangle of at most unit size, w by h, at `origin` in the frame turned by
+// (sin, cos), centred over the silhouette
+#[derive(Debug,Clone)]
+struct Frame {
+	sin: BigRational,
+	cos: BigRational,
+	origin: Point<BigRational>,
+	w: BigRational,
+	h: BigRational,
+}
+
+impl Frame {
+	fn new(points: &Vec<Point<BigRational>>, sin: BigRational, cos: BigRational) -> Frame {
+		let to_frame = Matrix33::rotate(-sin.clone(), cos.clone());
+		let pts: Vec<Point<BigRational>> = points.iter().map(|p| to_frame.transform(p.clone())).collect();
+		let (mut min, mut max) = (pts[0].clone(), pts[0].clone());
+		for p in pts.iter() {
+			if p.x < min.x { min.x = p.x.clone(); }
+			if p.y < min.y { min.y = p.y.clone(); }
+			if p.x > max.x { max.x = p.x.clone(); }
+			if p.y > max.y { max.y = p.y.clone(); }
+		}
+		let one = BigRational::one();
+		let two = one.clone() + one.clone();
+		let (dx, dy) = (max.x - min.x.clone(), max.y - min.y.clone());
+		let w = if dx < one { dx.clone() } else { one.clone() };
+		let h = if dy < one { dy.clone() } else { one.clone() };
+		let origin = Point{x: min.x + (dx - w.clone()) / two.clone(), y: min.y + (dy - h.clone()) / two};
+		Frame{sin: sin, cos: cos, origin: origin, w: w, h: h}
+	}
+
+	// from frame coordinates to the destination
+	fn transform(&self) -> Matrix33<BigRational> {
+		Matrix33::translate(self.origin.x.clone(), self.origin.y.clone()).then_rotate(self.sin.clone(), self.cos.clone())
+	}
+
+	fn rectangle(&self) -> Polygon<BigRational> {
+		let zero = BigRational::zero();
+		let corners = vec![
+			Point{x: zero.clone(), y: zero.clone()}, Point{x: self.w.clone(), y: zero.clone()},
+			Point{x: self.w.clone(), y: self.h.clone()}, Point{x: zero, y: self.h.clone()}
+		];
+		let transform = self.transform();
+		Polygon::new(corners.into_iter().map(|p| transform.transform(p)).collect())
+	}
+}
+
+// Moves folded paper by m, keeping track of where it came from
+pub fn moved(state: Vec<Polygon<BigRational>>, m: &Matrix33<BigRational>) -> Vec<Polygon<BigRational>> {
+	state.into_iter().map(|poly| {
+		let points = poly.points.into_iter().map(|p| m.transform(p)).collect();
+		Polygon::with_transform(points, poly.transform * m.clone())
+	}).collect()
+}
+
+pub fn unit_square() -> Polygon<BigRational> {
+	let (zero, one) = (BigRational::zero(), BigRational::one());
+	Polygon::new(vec![
+		Point{x: zero.clone(), y: zero.clone()}, Point{x: one.clone(), y: zero.clone()},
+		Point{x: one.clone(), y: one.clone()}, Point{x: zero, y: one}
+	])
+}
+
+// The unit square pleated down to the w by h rectangle at the origin, using at
+// most max_folds folds along each side, and whether it got there
+fn fold_to_rectangle(w: &BigRational, h: &BigRational, max_folds: usize, budget: &Budget) -> (Vec<Polygon<BigRational>>, bool) {
+	let (zero, one) = (BigRational::zero(), BigRational::one());
+	let origin = Point{x: zero.clone(), y: zero.clone()};
+	let up = Line::new(origin.clone(), Point{x: zero.clone(), y: one.clone()});
+	let across = Line::new(origin, Point{x: one, y: zero.clone()});
+	let (state, fits_across) = pleat(vec![unit_square()], &up, &Point{x: w.clone(), y: zero.clone()}, max_folds, budget);
+	let (state, fits_up) = pleat(state, &across, &Point{x: zero, y: h.clone()}, max_folds, budget);
+	(state, fits_across && fits_up)
+}
+
+// Bounding rectangle in the axis frame and in the frame of each hull edge,
+// best overlap with the silhouette first. Once the budget runs out only the
+// frames scored so far are left, always at least the axis one.
+fn frames(shape: &Shape<BigRational>, budget: &Budget) -> Vec<(Frame, f64)> {
+	let points: Vec<Point<BigRational>> = shape.polys.iter().filter(|poly| !poly.is_hole())
+		.flat_map(|poly| poly.points.iter().cloned()).collect();
+	if points.is_empty() {
+		return Vec::new();
+	}
+	let hull = convex_hull(&points);
+	let mut directions = vec![Point{x: BigRational::one(), y: BigRational::zero()}];
+	for edge in hull.edges() {
+		if let Some(u) = rational_direction(&(&edge.p2 - &edge.p1)) {
+			directions.push(u);
+		}
+	}
+	let mut frames: Vec<(Frame, f64)> = Vec::new();
+	for u in directions {
+		if !frames.is_empty() && budget.expired() {
+			break;
+		}
+		let frame = Frame::new(&points, u.y, u.x);
+		let score = sampled_resemblance(shape, &vec![frame.rectangle()], SCORING_SAMPLES);
+		frames.push((frame, score));
+	}
+	frames.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+	frames
+}
+
+pub struct BboxStrategy;
+
+impl Strategy for BboxStrategy {
+	fn name(&self) -> &'static str { "bbox" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		problem.shape.polys.iter().any(|poly| !poly.is_hole())
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		let frame = match frames(&problem.shape, budget).into_iter().next() {
+			Some((frame, _)) => frame,
+			None => return None
+		};
+		// more folds until the paper fits the rectangle. Fewer leave it bigger,
+		// but the unfolded square is always small enough to submit.
+		let (mut max_folds, mut best) = (0, None);
+		loop {
+			let (state, fits) = fold_to_rectangle(&frame.w, &frame.h, max_folds, budget);
+			match Solution::new(self.name(), &problem.shape, moved(state, &frame.transform()), BigInt::from(1)) {
+				Ok(solution) if fits => return Some(solution),
+				Ok(solution) => best = Some(solution),
+				Err(e) => {
+					// too big, so the last one will have to do
+					if best.is_none() {
+						println!("{}: {:?}", self.name(), e);
+					}
+					return best;
+				}
+			}
+			if max_folds >= MAX_FOLDS || budget.expired() {
+				return best;
+			}
+			max_folds = if max_folds == 0 { 1 } else { max_folds * 2 };
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[test]
+	fn test_fold_to_rectangle() {
+		let (state, fits) = fold_to_rectangle(&rat(1, 3), &rat(2, 5), MAX_FOLDS, &Budget::unlimited());
+		assert!(fits);
+		// three strips across, three down (the last one only partly covered)
+		assert_eq!(9, state.len());
+		let mut area = rat(0, 1);
+		for poly in state.iter() {
+			assert!(poly.signed_area() > rat(0, 1));
+			for pt in poly.points.iter() {
+				assert!(pt.x >= rat(0, 1) && pt.x <= rat(1, 3) && pt.y >= rat(0, 1) && pt.y <= rat(2, 5));
+			}
+			area = area + poly.source_poly().signed_area().abs();
+		}
+		assert_eq!(rat(1, 1), area);
+	}
+
+	#[test]
+	fn test_bbox_strategy() {
+		// a thin diagonal sliver of a 3-4-5 triangle, off somewhere else
+		let sliver = Polygon::new(vec![p(rat(5, 1), rat(5, 1)), p(rat(28, 5), rat(29, 5)), p(rat(27, 5), rat(29, 5))]);
+		let problem = Problem{shape: Shape::new(vec![sliver]), skeleton: Skeleton::new(Vec::new())};
+		let solution = BboxStrategy.attempt(&problem, &Budget::unlimited()).unwrap();
+		assert!(solution.resemblance > 0.2);
+		// out of time there's still the square, unfolded
+		let budget = Budget::unlimited();
+		budget.cancel();
+		let unfolded = BboxStrategy.attempt(&problem, &budget).unwrap();
+		assert!(unfolded.resemblance < solution.resemblance);
+	}
+}
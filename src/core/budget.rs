@@ -0,0 +1,85 @@
+// How much work a solver may do: a deadline, a number of steps, or both.
+// Clones share the step count, so a budget handed to several threads is spent
+// by all of them together, and cancelling any one of them cancels the lot.
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool,AtomicUsize};
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::{Duration,Instant};
+
+#[derive(Debug,Clone)]
+pub struct Budget {
+	pub deadline: Option<Instant>,
+	pub max_steps: Option<usize>,
+	steps: Arc<AtomicUsize>,
+	cancelled: Arc<AtomicBool>,
+}
+
+impl Budget {
+	pub fn unlimited() -> Budget {
+		Budget{deadline: None, max_steps: None, steps: Arc::new(AtomicUsize::new(0)), cancelled: Arc::new(AtomicBool::new(false))}
+	}
+
+	pub fn with_limit(limit: Duration) -> Budget {
+		let mut budget = Budget::unlimited();
+		budget.deadline = Some(Instant::now() + limit);
+		budget
+	}
+
+	pub fn limit_steps(mut self, max_steps: usize) -> Budget {
+		self.max_steps = Some(max_steps);
+		self
+	}
+
+	// Counts one step of work, and says whether there's any budget left for it
+	pub fn step(&self) -> bool {
+		self.steps.fetch_add(1, SeqCst);
+		!self.expired()
+	}
+
+	pub fn steps(&self) -> usize {
+		self.steps.load(SeqCst)
+	}
+
+	// Tells everyone sharing the budget to stop
+	pub fn cancel(&self) {
+		self.cancelled.store(true, SeqCst);
+	}
+
+	pub fn expired(&self) -> bool {
+		if self.cancelled.load(SeqCst) {
+			return true;
+		}
+		if let Some(max) = self.max_steps {
+			if self.steps() > max {
+				return true;
+			}
+		}
+		match self.deadline {
+			Some(deadline) => Instant::now() >= deadline,
+			None => false
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_budget() {
+		let budget = Budget::unlimited().limit_steps(2);
+		assert!(budget.step());
+		let shared = budget.clone();
+		assert!(shared.step());
+		assert!(!budget.step());
+		assert_eq!(3, shared.steps());
+		assert!(shared.expired());
+
+		let budget = Budget::unlimited();
+		budget.clone().cancel();
+		assert!(budget.expired());
+
+		assert!(Budget::with_limit(Duration::from_secs(0)).expired());
+		assert!(!Budget::with_limit(Duration::from_secs(60)).expired());
+	}
+}
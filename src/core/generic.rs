@@ -14,13 +14,15 @@ use num::rational::BigRational;
 use num::ToPrimitive;
 use self::num::bigint::BigInt;
 
-pub trait SuperLegit {
+pub trait SuperLegit: Sized {
 	fn to_f64(&self) -> f64;
     fn to_rat(&self) -> BigRational;
 	fn from_f64(f64) -> Self;
 	fn zero() -> Self;
 	fn one() -> Self;
 	fn abs(&self) -> Self;
+	// exact square root, or None when it isn't representable
+	fn sqrt(&self) -> Option<Self>;
 }
 
 impl SuperLegit for i32 {
@@ -30,6 +32,10 @@ impl SuperLegit for i32 {
 	fn zero() -> Self { 0 }
 	fn one() -> Self { 1 }
 	fn abs(&self) -> Self { if self < &0 { -self } else { *self }}
+	fn sqrt(&self) -> Option<Self> {
+		let r = (*self as f64).sqrt().round() as i32;
+		if r * r == *self { Some(r) } else { None }
+	}
 }
 
 impl SuperLegit for f64 {
@@ -39,6 +45,7 @@ impl SuperLegit for f64 {
 	fn zero() -> Self { 0.0 }
 	fn one() -> Self { 1.0 }
 	fn abs(&self) -> Self { if self < &0.0 { -self } else { *self } }
+	fn sqrt(&self) -> Option<Self> { if *self < 0.0 { None } else { Some(f64::sqrt(*self)) } }
 }
 
 pub fn divide<N:Num>( a: N, b: N ) -> Option<N> {
@@ -125,6 +132,27 @@ impl SuperLegit for BigRational {
 	fn zero() -> Self { num::zero::<BigRational>() }
 	fn one() -> Self { num::one::<BigRational>() }
 	fn abs(&self) -> Self { if self < &Self::zero() { -self } else { self.clone() }}
+	fn sqrt(&self) -> Option<Self> {
+		// rationals in lowest terms only have a rational root if both parts are squares
+		match (isqrt(self.numer()), isqrt(self.denom())) {
+			(Some(n), Some(d)) => Some(BigRational::new(n, d)),
+			_ => None
+		}
+	}
+}
+
+// integer square root by newton's method, None unless n is a perfect square
+fn isqrt(n: &BigInt) -> Option<BigInt> {
+	let zero = BigInt::from(0);
+	if n < &zero { return None }
+	if n == &zero { return Some(zero) }
+	let mut x = BigInt::from(1) << (n.bits() / 2 + 1);
+	loop {
+		let y = (x.clone() + n / &x) / BigInt::from(2);
+		if y >= x { break }
+		x = y;
+	}
+	if &(x.clone() * x.clone()) == n { Some(x) } else { None }
 }
 
 pub trait Num: Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> + Neg<Output=Self> + Sized + FromStr + Debug + Display + PartialOrd + PartialEq + Clone + SuperLegit {}
@@ -247,6 +275,15 @@ mod tests {
         
     }
     
+	#[test]
+	fn test_sqrt() {
+		assert_eq!(Some(BigRational::new(BigInt::from(3), BigInt::from(5))), BigRational::new(BigInt::from(9), BigInt::from(25)).sqrt());
+		assert_eq!(None, BigRational::new(BigInt::from(1), BigInt::from(2)).sqrt());
+		assert_eq!(None, BigRational::new(BigInt::from(-4), BigInt::from(1)).sqrt());
+		assert_eq!(Some(12), 144.sqrt());
+		assert_eq!(None, 7.sqrt());
+	}
+
 	#[test]
 	fn test_commutivity() {
 		let (p1, p2) = (p(1.0, 1.5), p(1.25, 2.5));
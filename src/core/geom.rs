@@ -2,6 +2,7 @@ use super::*;
 
 use super::super::matrix::Matrix33;
 
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
 
 #[derive(Debug,Clone,PartialOrd)]
@@ -58,7 +59,7 @@ pub fn intersect_inf<N:Num>(a: &Line<N>, b: &Line<N>) -> Option<Point<N>> {
   Some(Point{x: x_out, y: y_out})
 }
 
-fn cross_scalar<N: Num>(a: &Point<N>, b: &Point<N>) -> N {
+pub fn cross_scalar<N: Num>(a: &Point<N>, b: &Point<N>) -> N {
 	a.x.clone() * b.y.clone() - a.y.clone() * b.x.clone()
 }
 
@@ -143,33 +144,20 @@ pub fn intersect_poly_inf<N:Num>(line: Line<N>, other: &Polygon<N>) -> Vec<(Poin
 	intersect_poly(line, other, false)
 }
 
-pub fn gradient<N:Num>(l: &Line<N>) -> Option<N> {
-	divide(  l.p2.y.clone() - l.p1.y.clone(),  l.p2.x.clone() - l.p1.x.clone() )
-}
-
+// Reflection about the line through vertex1 and vertex2. For a line through
+// the origin with direction d this is [[dx²-dy², 2dxdy], [2dxdy, dy²-dx²]] / |d|²,
+// so rational lines give exact rational reflections (no square roots needed).
 pub fn reflect_matrix<N:Num>(vertex1: &Point<N>, vertex2: &Point<N>) -> Matrix33<N> {
-    let l = Line::new(vertex1.clone(),vertex2.clone());
-    
-    let m = match gradient(&l) {
-        Some(g) =>{
-            let c = vertex1.y.clone() - g.clone() * vertex1.x.clone();
-            let d = vertex2 - vertex1;
-
-            Matrix33::translate(N::zero(),-c.clone())
-                .then_rotate( - d.clone().y / v_distance(&d), d.clone().x / v_distance(&d) )
-                .then_scale(N::one(),N::from_f64(-1.0))
-                .then_rotate( d.clone().y / v_distance(&d), d.clone().x / v_distance(&d) )
-                .then_translate(N::zero(),c.clone())
-        }
-        None => {
-            Matrix33::rotate(N::one(),N::zero())
-                .then_scale(N::one(),N::from_f64(-1.0))
-                .then_rotate(N::from_f64(-1.0),N::zero())
-        }
-    };
-
-    //println!("{}", m);
-    return m;
+	let d = vertex2 - vertex1;
+	let len2 = d.dot(d.clone());
+	let c = (d.x.clone() * d.x.clone() - d.y.clone() * d.y.clone()) / len2.clone();
+	let s = (d.x.clone() * d.y.clone() + d.x.clone() * d.y.clone()) / len2;
+	let reflect = Matrix33::new(
+		(c.clone(), s.clone(), N::zero()),
+		(s, -c, N::zero()),
+		(N::zero(), N::zero(), N::one())
+	);
+	Matrix33::translate(-vertex1.x.clone(), -vertex1.y.clone()) * reflect * Matrix33::translate(vertex1.x.clone(), vertex1.y.clone())
 }
 
 //flips both points of a line on an axis
@@ -205,17 +193,23 @@ pub fn flip_polygon<N: Num>(poly: &Polygon<N>, vertex1: &Point<N>, vertex2: &Poi
 	ret
 }
 
-pub fn fold_polygon<N: Num>(poly: &Polygon<N>, vertex1: &Point<N>, vertex2: &Point<N>, anchor: &Point<N>) -> Vec<Polygon<N>> {
-	let mut polys = split_polygon(&poly,&vertex1,&vertex2);
+// None if the budget ran out before the polygon was split
+pub fn fold_polygon<N: Num>(poly: &Polygon<N>, vertex1: &Point<N>, vertex2: &Point<N>, anchor: &Point<N>, budget: &Budget) -> Option<Vec<Polygon<N>>> {
+	let mut polys = match split_polygon(&poly,&vertex1,&vertex2,budget) {
+		Some(polys) => polys,
+		None => return None
+	};
 	for poly in polys.iter_mut() {
 		if !poly.contains(anchor) {
 			*poly = flip_polygon(&poly, &vertex1, &vertex2);
 		}
 	}
-	polys
+	Some(polys)
 }
 
-pub fn split_polygon<N: Num>(poly: &Polygon<N>, v1: &Point<N>, v2: &Point<N>) -> Vec<Polygon<N>> {
+// Cuts poly along the line through v1 and v2, one step of the budget per edge.
+// None if the budget ran out part way.
+pub fn split_polygon<N: Num>(poly: &Polygon<N>, v1: &Point<N>, v2: &Point<N>, budget: &Budget) -> Option<Vec<Polygon<N>>> {
 	let mut polys = Vec::new();
 	let mut resume = BTreeMap::new();
 	let fold = Line::new(v1.clone(), v2.clone());
@@ -227,6 +221,9 @@ pub fn split_polygon<N: Num>(poly: &Polygon<N>, v1: &Point<N>, v2: &Point<N>) ->
 	let mut cur = 0;
 
 	for edge in poly.edges() {
+		if !budget.step() {
+			return None;
+		}
 		//println!("starting edge {}; on poly {}/{}", edge, cur+1, polys.len());
 		polys[cur].push(edge.p1.clone());
 
@@ -260,7 +257,75 @@ pub fn split_polygon<N: Num>(poly: &Polygon<N>, v1: &Point<N>, v2: &Point<N>) ->
 		}
 	}
 
-	polys.into_iter().map(|points| Polygon::with_transform(points, poly.transform.clone())).collect()
+	Some(polys.into_iter().map(|points| Polygon::with_transform(points, poly.transform.clone())).collect())
+}
+
+// Exactly splits a convex polygon by the infinite line through `line`.
+// Returns the pieces to the left and right of line.p1 -> line.p2; either is
+// None when the polygon doesn't extend to that side of the line.
+pub fn split_convex<N: Num>(poly: &Polygon<N>, line: &Line<N>) -> (Option<Polygon<N>>, Option<Polygon<N>>) {
+	let (mut left, mut right) = (Vec::new(), Vec::new());
+	let (mut any_left, mut any_right) = (false, false);
+	for edge in poly.edges() {
+		let (s1, s2) = (side(line, &edge.p1), side(line, &edge.p2));
+		if s1 >= N::zero() { left.push(edge.p1.clone()); }
+		if s1 <= N::zero() { right.push(edge.p1.clone()); }
+		any_left = any_left || s1 > N::zero();
+		any_right = any_right || s1 < N::zero();
+		if (s1 > N::zero() && s2 < N::zero()) || (s1 < N::zero() && s2 > N::zero()) {
+			let t = s1.clone() / (s1 - s2);
+			let cut = &edge.p1 + (&edge.p2 - &edge.p1).scale(t);
+			left.push(cut.clone());
+			right.push(cut);
+		}
+	}
+	let piece = |points: Vec<Point<N>>, any: bool| if any { Some(Polygon::with_transform(points, poly.transform.clone())) } else { None };
+	(piece(left, any_left), piece(right, any_right))
+}
+
+// True if the interiors of two counter-clockwise convex polygons intersect
+pub fn convex_overlap<N: Num>(a: &Polygon<N>, b: &Polygon<N>) -> bool {
+	let mut clipped = a.clone();
+	for edge in b.edges() {
+		match split_convex(&clipped, &edge).0 {
+			Some(inside) => clipped = inside,
+			None => return false
+		}
+	}
+	clipped.signed_area() > N::zero()
+}
+
+// Which side of the infinite line `l` the point lies on: positive to the left
+// of l.p1 -> l.p2, negative to the right and zero when coincident.
+pub fn side<N: Num>(l: &Line<N>, p: &Point<N>) -> N {
+	cross_scalar(&(&l.p2 - &l.p1), &(p - &l.p1))
+}
+
+// Convex hull via Andrew's monotone chain. Returned counter-clockwise (ie. not
+// a hole) with collinear points dropped.
+pub fn convex_hull<N: Num>(points: &Vec<Point<N>>) -> Polygon<N> {
+	let mut sorted = points.clone();
+	sorted.sort();
+	sorted.dedup();
+	if sorted.len() < 3 {
+		return Polygon::new(sorted);
+	}
+	let mut hull: Vec<Point<N>> = Vec::new();
+	for pass in 0..2 {
+		let start = hull.len();
+		for p in sorted.iter() {
+			while hull.len() >= start + 2 && side(&Line::new(hull[hull.len()-2].clone(), hull[hull.len()-1].clone()), p) <= N::zero() {
+				hull.pop();
+			}
+			hull.push(p.clone());
+		}
+		// the last point of each chain starts the next one
+		hull.pop();
+		if pass == 0 {
+			sorted.reverse();
+		}
+	}
+	Polygon::new(hull)
 }
 
 pub fn p_distance<N: Num>(p1: &Point<N>, p2: &Point<N>) -> N {
@@ -319,8 +384,55 @@ impl<N: Num> Polygon<N> {
 		return sum.to_f64();
 	}
 
-	pub fn area(&self) -> f64 {
-		return (self.double_signed_area() / 2.0_f64).abs();
+	// Exact signed area, positive for counter-clockwise polygons
+	pub fn signed_area(&self) -> N {
+		let mut sum = N::zero();
+		for edge in self.edges() {
+			sum = sum + cross_scalar(&edge.p1, &edge.p2);
+		}
+		sum / (N::one() + N::one())
+	}
+
+	// Ear clipping triangulation of a simple counter-clockwise polygon
+	pub fn triangulate(&self) -> Vec<Polygon<N>> {
+		let mut pts = self.points.clone();
+		let mut tris = Vec::new();
+		loop {
+			// collinear vertices can't be ears, and don't change the shape
+			let n = pts.len();
+			if n < 3 {
+				break;
+			}
+			if let Some(i) = (0..n).find(|&i| side(&Line::new(pts[(i+n-1)%n].clone(), pts[i].clone()), &pts[(i+1)%n]) == N::zero()) {
+				pts.remove(i);
+				continue;
+			}
+			let ear = (0..n).find(|&i| {
+				let (a, b, c) = (&pts[(i+n-1)%n], &pts[i], &pts[(i+1)%n]);
+				let (ab, bc, ca) = (Line::new(a.clone(), b.clone()), Line::new(b.clone(), c.clone()), Line::new(c.clone(), a.clone()));
+				side(&ab, c) > N::zero() && !pts.iter().any(|q| {
+					q.cmp(a) != Ordering::Equal && q.cmp(b) != Ordering::Equal && q.cmp(c) != Ordering::Equal &&
+						side(&ab, q) >= N::zero() && side(&bc, q) >= N::zero() && side(&ca, q) >= N::zero()
+				})
+			});
+			match ear {
+				Some(i) => {
+					tris.push(Polygon::with_transform(vec![pts[(i+n-1)%n].clone(), pts[i].clone(), pts[(i+1)%n].clone()], self.transform.clone()));
+					pts.remove(i);
+				},
+				None => break
+			}
+		}
+		tris
+	}
+
+	// A point strictly inside this (counter-clockwise) polygon
+	pub fn interior_point(&self) -> Option<Point<N>> {
+		self.triangulate().first().map(|tri| {
+			let three = N::one() + N::one() + N::one();
+			let sum = &(&tri.points[0] + &tri.points[1]) + &tri.points[2];
+			Point{x: sum.x / three.clone(), y: sum.y / three}
+		})
 	}
 
 	pub fn printcongruency(&self) {
@@ -353,15 +465,6 @@ impl<N: Num> Polygon<N> {
 		return self.double_signed_area() >= 0.0;
 	}
 
-	pub fn square(&self) -> bool {
-		if self.corners().len() == 4 {
-			if self.edges().len() == 4{
-				return true
-			}
-		}
-		return false
-	}
-
 	pub fn corners(&self) -> Vec<(Line<N>, Line<N>)> {
 		let edges = self.edges();
 		let mut corners: Vec<(Line<N>, Line<N>)> = Vec::new();
@@ -459,13 +562,14 @@ impl<N: Num> Shape<N> {
 		Shape{polys: polys}
 	}
 
+	// Whether a point lies within the silhouette, excluding holes
+	pub fn contains(&self, p: &Point<N>) -> bool {
+		self.polys.iter().filter(|poly| poly.inside(p)).count() % 2 == 1
+	}
+
+	// holes wind clockwise, so their signed area takes itself off
 	pub fn area(self) -> f64 {
-		let mut a = 0.0;
-		for p in self.polys {
-			let sgn = if p.is_hole() { -1.0 } else { 1.0 };
-			a += sgn * p.area();
-		}
-		a
+		self.polys.iter().fold(0.0, |a, p| a + p.signed_area().to_f64())
 	}
 }
 
@@ -545,15 +649,6 @@ mod tests {
     assert!(!(p(1.0,1.0) == p(0.0000000001,0.0000000001)));
   }
 
-	#[test]
-	fn gradient_test(){
-        let p1 = p(1,1);
-        let p2 = p(0,0);
-        let g = gradient(&Line{p1:p1,p2:p2});
-        println!("gradient_test: {:?}",g);
-        assert_eq!(g,Some(1));
-	}
-
 	#[test]
 	fn test_flip_point_matrix(){
 		let mut p2 = reflect_matrix(&p(0.0,0.0), &p(1.0,0.0)).transform(p(1.0,1.0));
@@ -599,7 +694,7 @@ mod tests {
 		let poly = Polygon::new(vec!( p(0.0,0.0),p(2.0,0.0),p(2.0,2.0),p(0.0,2.0) ));
 		let v1 = p(0.0,1.0);
 		let v2 = p(2.0,1.0);
-		let ret = fold_polygon(&poly,&v1,&v2,&p(0.0, 2.0));
+		let ret = fold_polygon(&poly,&v1,&v2,&p(0.0, 2.0),&Budget::unlimited()).unwrap();
 
 		println!("fold_polygon_test: {:?}",ret);
 
@@ -658,10 +753,10 @@ mod tests {
 
 	#[test]
 	fn test_area() {
-		assert_eq!(1.0, Polygon::new(vec!(p(0, 0), p(1, 0), p(1, 1), p(0, 1))).area());
-		assert_eq!(1.0, Polygon::new(vec!(p(0, 0), p(0, 1), p(1, 1), p(1, 0))).area());
+		assert_eq!(1, Polygon::new(vec!(p(0, 0), p(1, 0), p(1, 1), p(0, 1))).signed_area());
+		assert_eq!(-1, Polygon::new(vec!(p(0, 0), p(0, 1), p(1, 1), p(1, 0))).signed_area());
 		let p22 = Polygon::new(vec!(p(0, 0), p(2, 0), p(2, 2), p(0, 2)));
-		assert_eq!(4.0, p22.area());
+		assert_eq!(4, p22.signed_area());
 		let p44 = Polygon::new(vec!(p(0, 0), p(4, 0), p(4, 4), p(0, 4)));
 		let hole12 = Polygon::new(vec!(p(1, 1), p(1, 2), p(2, 2), p(2, 1)));
 		assert!(hole12.is_hole());
@@ -804,6 +899,55 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_split_convex() {
+		let sq = Polygon::new(vec!(p(0.0, 0.0), p(2.0, 0.0), p(2.0, 2.0), p(0.0, 2.0)));
+		let (left, right) = split_convex(&sq, &Line::new(p(1.0, 0.0), p(1.0, 1.0)));
+		assert_eq!(vec!(p(0.0, 2.0), p(0.0, 0.0), p(1.0, 0.0), p(1.0, 2.0)), left.unwrap().points);
+		assert_eq!(vec!(p(1.0, 0.0), p(2.0, 0.0), p(2.0, 2.0), p(1.0, 2.0)), right.unwrap().points);
+
+		// line along an edge leaves the whole polygon on one side
+		let (left, right) = split_convex(&sq, &Line::new(p(0.0, 0.0), p(2.0, 0.0)));
+		assert_eq!(4, left.unwrap().points.len());
+		assert!(right.is_none());
+	}
+
+	#[test]
+	fn test_convex_hull() {
+		let pts = vec!(p(0, 0), p(2, 0), p(1, 1), p(2, 2), p(1, 2), p(0, 2), p(1, 0));
+		let hull = convex_hull(&pts);
+		assert_eq!(vec!(p(0, 0), p(2, 0), p(2, 2), p(0, 2)), hull.points);
+		assert!(!hull.is_hole());
+	}
+
+	#[test]
+	fn test_reflect_exact() {
+		let r = reflect_matrix(&p(rat(0, 1), rat(1, 2)), &p(rat(4, 1), rat(7, 2)));
+		let q = r.transform(p(rat(1, 1), rat(0, 1)));
+		assert_eq!(p(rat(-1, 5), rat(8, 5)), q);
+		assert_eq!(p(rat(1, 1), rat(0, 1)), r.transform(q));
+	}
+
+	#[test]
+	fn test_triangulate() {
+		// L shape, with a collinear point on the bottom edge
+		let l = Polygon::new(vec!(p(0.0, 0.0), p(1.0, 0.0), p(2.0, 0.0), p(2.0, 1.0), p(1.0, 1.0), p(1.0, 2.0), p(0.0, 2.0)));
+		let tris = l.triangulate();
+		assert_eq!(4, tris.len());
+		assert_eq!(3.0, tris.iter().map(|t| t.signed_area()).sum::<f64>());
+		assert!(l.inside(&l.interior_point().unwrap()));
+	}
+
+	#[test]
+	fn test_convex_overlap() {
+		let a = Polygon::new(vec!(p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)));
+		let b = Polygon::new(vec!(p(0.5, 0.0), p(1.5, 0.0), p(1.5, 1.0), p(0.5, 1.0)));
+		let c = Polygon::new(vec!(p(1.0, 0.0), p(2.0, 0.0), p(2.0, 1.0), p(1.0, 1.0)));
+		assert!(convex_overlap(&a, &b));
+		assert!(convex_overlap(&a, &a));
+		assert!(!convex_overlap(&a, &c));
+	}
+
 	#[test]
 	fn test_normalize_line() {
 		let (p1, p2) = (p(1.0, 1.5), p(0.5, 0.0));
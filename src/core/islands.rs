@@ -0,0 +1,129 @@
+// Silhouettes made of several separate pieces. The paper can rarely cover all
+// of them well at once, so try covering each piece, or a piece along with one
+// of its neighbours, and keep whichever resembles the whole silhouette best.
+// Each piece keeps its holes, so they count against paper covering them and
+// their edges are folded along like any other. Pieces aren't joined with thin
+// strips of paper, since narrowing just part of the paper takes folds that
+// don't run right across it. Instead the whole silhouette gets wrapped in its
+// hull first, gaps and all, which wins when the pieces lie close together.
+use super::*;
+
+use std::cmp::Ordering;
+
+// most groups of islands tried by the strategy
+const MAX_GROUPS: usize = 8;
+
+// Splits a silhouette into islands, each an outline with the holes directly
+// inside it. Biggest island first.
+pub fn islands<N: Num>(shape: &Shape<N>) -> Vec<Shape<N>> {
+	let mut outlines: Vec<Polygon<N>> = shape.polys.iter().filter(|poly| !poly.is_hole()).cloned().collect();
+	outlines.sort_by(|a, b| b.signed_area().partial_cmp(&a.signed_area()).unwrap_or(Ordering::Equal));
+	let mut parts: Vec<Vec<Polygon<N>>> = outlines.iter().map(|outline| vec![outline.clone()]).collect();
+	for hole in shape.polys.iter().filter(|poly| poly.is_hole()) {
+		let mut ccw = hole.clone();
+		ccw.points.reverse();
+		let inner = match ccw.interior_point() {
+			Some(p) => p,
+			None => continue
+		};
+		// the smallest outline around the hole is the one it's cut out of
+		if let Some(i) = (0..outlines.len()).rev().find(|&i| outlines[i].inside(&inner)) {
+			parts[i].push(hole.clone());
+		}
+	}
+	parts.into_iter().map(Shape::new).collect()
+}
+
+pub struct IslandStrategy {
+	pub beam: BeamOptions,
+}
+
+impl Strategy for IslandStrategy {
+	fn name(&self) -> &'static str { "islands" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		islands(&problem.shape).len() > 1
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		let parts = islands(&problem.shape);
+		// all the islands together, each alone, then the biggest along with
+		// each of the others unless that's all of them already
+		let mut groups: Vec<Vec<usize>> = vec![(0..parts.len()).collect()];
+		groups.extend((0..parts.len()).map(|i| vec![i]));
+		if parts.len() > 2 {
+			groups.extend((1..parts.len()).map(|i| vec![0, i]));
+		}
+		groups.truncate(MAX_GROUPS);
+
+		let portfolio = Portfolio::new().add(HullStrategy).add(BeamStrategy{options: self.beam.clone()});
+		let mut best: Option<Solution> = None;
+		for group in groups {
+			if budget.expired() {
+				break;
+			}
+			let polys = group.iter().flat_map(|&i| parts[i].polys.iter().cloned()).collect();
+			let sub = Problem{shape: Shape::new(polys), skeleton: problem.skeleton.clone()};
+			if let Some(solution) = portfolio.run(&sub, budget) {
+				let solution = solution.rescored(&problem.shape);
+				if best.as_ref().map_or(true, |b| solution.resemblance > b.resemblance) {
+					best = Some(solution);
+				}
+			}
+		}
+		best.map(|mut solution| {
+			solution.strategy = self.name().to_string();
+			solution
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn square(x: i64, y: i64, size: BigRational) -> Polygon<BigRational> {
+		let (x, y) = (rat(x, 1), rat(y, 1));
+		Polygon::new(vec![
+			p(x.clone(), y.clone()), p(x.clone() + size.clone(), y.clone()),
+			p(x.clone() + size.clone(), y.clone() + size.clone()), p(x, y + size)
+		])
+	}
+
+	#[test]
+	fn test_islands() {
+		let mut hole = square(0, 0, rat(1, 2));
+		hole.points.reverse();
+		let shape = Shape::new(vec![square(5, 5, rat(1, 2)), hole, square(-1, -1, rat(3, 1))]);
+		let parts = islands(&shape);
+		assert_eq!(2, parts.len());
+		// the big one comes first, with its hole
+		assert_eq!(2, parts[0].polys.len());
+		assert_eq!(rat(9, 1), parts[0].polys[0].signed_area());
+		assert_eq!(1, parts[1].polys.len());
+	}
+
+	#[test]
+	fn test_island_strategy() {
+		// a unit square and a small one far away: covering the first exactly
+		// gets 1 / (1 + 1/4), give or take the sampling over such a big area
+		let shape = Shape::new(vec![square(5, 5, rat(1, 2)), square(0, 0, rat(1, 1))]);
+		let problem = Problem{shape: shape, skeleton: Skeleton::new(Vec::new())};
+		let solution = IslandStrategy{beam: BeamOptions::default()}.attempt(&problem, &Budget::unlimited()).unwrap();
+		assert_eq!("islands", solution.strategy);
+		assert!((solution.resemblance - 0.8).abs() < 0.02);
+
+		// stripes across the paper with thin gaps between them: the hull of
+		// the lot is the paper itself, better than any stripe on its own
+		let stripe = |y1: BigRational, y2: BigRational| Polygon::new(vec![p(rat(0, 1), y1.clone()), p(rat(1, 1), y1.clone()), p(rat(1, 1), y2.clone()), p(rat(0, 1), y2)]);
+		for stripes in vec![
+			vec![stripe(rat(0, 1), rat(9, 20)), stripe(rat(11, 20), rat(1, 1))],
+			vec![stripe(rat(0, 1), rat(3, 10)), stripe(rat(7, 20), rat(13, 20)), stripe(rat(7, 10), rat(1, 1))]
+		] {
+			let problem = Problem{shape: Shape::new(stripes), skeleton: Skeleton::new(Vec::new())};
+			let solution = IslandStrategy{beam: BeamOptions{depth: 1, width: 2}}.attempt(&problem, &Budget::unlimited()).unwrap();
+			assert!((solution.resemblance - 0.9).abs() < 0.02);
+		}
+	}
+}
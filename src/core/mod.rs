@@ -1,12 +1,34 @@
 /* vim: set noexpandtab : */
 
+mod arrangement;
+mod bbox;
+mod budget;
 mod generic;
 mod geom;
+mod islands;
+mod placement;
+mod pleat;
+mod resemblance;
+mod search;
+mod silhouette;
 mod solve;
+mod unfold;
+mod validate;
 
+pub use self::arrangement::*;
+pub use self::bbox::*;
+pub use self::budget::*;
 pub use self::generic::*;
 pub use self::geom::*;
+pub use self::islands::*;
+pub use self::placement::*;
+pub use self::pleat::*;
+pub use self::resemblance::*;
+pub use self::search::*;
+pub use self::silhouette::*;
 pub use self::solve::*;
+pub use self::unfold::*;
+pub use self::validate::*;
 
 /* Test helper functions go here. */
 #[cfg(test)]
@@ -15,8 +37,13 @@ mod tests {
 	extern crate num;
 	pub use self::num::rational::BigRational;
 	pub use num::Float;
+	use self::num::bigint::BigInt;
 
 	pub fn p<N: Num>(x: N, y: N) -> Point<N> {
 		Point{x: x, y: y}
 	}
+
+	pub fn rat(n: i64, d: i64) -> BigRational {
+		BigRational::new(BigInt::from(n), BigInt::from(d))
+	}
 }
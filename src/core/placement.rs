@@ -0,0 +1,198 @@
+// Where to put the unit square before folding it. Candidates are lined up with
+// the silhouette's edges (which covers its right-angled corners too), in every
+// orientation, and scored by how well the bare square overlaps the silhouette.
+use super::*;
+use super::super::matrix::Matrix33;
+
+use num::BigInt;
+use num::rational::BigRational;
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+const SCORING_SAMPLES: usize = 48;
+// largest denominator used when approximating an irrational direction
+const MAX_DENOM: i64 = 1000;
+
+#[derive(Debug,Clone)]
+pub struct Placement {
+	// the square with its transform from the unit square, which is mirrored
+	// for half of them. Mirror images fold the same but give a different
+	// source layout.
+	pub square: Polygon<BigRational>,
+	pub score: f64,
+}
+
+// Best rational approximation of x with a bounded denominator, from the
+// continued fraction convergents
+fn approximate(x: f64, max_denom: i64) -> BigRational {
+	let (mut h0, mut h1, mut k0, mut k1) = (0i64, 1i64, 1i64, 0i64);
+	let mut r = x;
+	loop {
+		let a = r.floor();
+		let (h2, k2) = (a as i64 * h1 + h0, a as i64 * k1 + k0);
+		if k2 > max_denom {
+			break;
+		}
+		h0 = h1; h1 = h2; k0 = k1; k1 = k2;
+		if (r - a).abs() < 1e-12 {
+			break;
+		}
+		r = 1.0 / (r - a);
+	}
+	BigRational::new(BigInt::from(h1), BigInt::from(k1))
+}
+
+// Unit vector along d. Exact when d has a rational length, otherwise the
+// nearest Pythagorean direction we find, so the placement stays exact.
+pub fn rational_direction(d: &Point<BigRational>) -> Option<Point<BigRational>> {
+	let len2 = d.dot(d.clone());
+	if len2 == BigRational::zero() {
+		return None;
+	}
+	if let Some(len) = len2.sqrt() {
+		return Some(Point{x: d.x.clone() / len.clone(), y: d.y.clone() / len});
+	}
+	// (cos, sin) = ((1 - t^2), 2t) / (1 + t^2) with t = tan(angle / 2). Keep the
+	// angle within 90 degrees so t stays small, and turn around afterwards.
+	let flip = d.x < BigRational::zero();
+	let f = d.to_f64();
+	let angle = if flip { (-f.y).atan2(-f.x) } else { f.y.atan2(f.x) };
+	let t = approximate((angle / 2.0).tan(), MAX_DENOM);
+	let one = BigRational::one();
+	let denom = one.clone() + t.clone() * t.clone();
+	let u = Point{x: (one - t.clone() * t.clone()) / denom.clone(), y: (t.clone() + t) / denom};
+	Some(if flip { Point{x: -u.x, y: -u.y} } else { u })
+}
+
+// The square with its corner at o, sides along u and w
+fn square(o: &Point<BigRational>, u: &Point<BigRational>, w: &Point<BigRational>) -> Polygon<BigRational> {
+	let (zero, one) = (BigRational::zero(), BigRational::one());
+	let transform = Matrix33::new(
+		(u.x.clone(), u.y.clone(), zero.clone()),
+		(w.x.clone(), w.y.clone(), zero.clone()),
+		(o.x.clone(), o.y.clone(), one.clone())
+	);
+	let unit = vec![
+		Point{x: zero.clone(), y: zero.clone()}, Point{x: one.clone(), y: zero.clone()},
+		Point{x: one.clone(), y: one.clone()}, Point{x: zero, y: one}
+	];
+	let mut points: Vec<Point<BigRational>> = unit.into_iter().map(|p| transform.transform(p)).collect();
+	// keep the outline counter-clockwise even when the paper is flipped over
+	if transform.det() < BigRational::zero() {
+		points.reverse();
+	}
+	Polygon::with_transform(points, transform)
+}
+
+// Every candidate placement, best first. Once the budget runs out only the
+// squares scored so far are left, always at least the first.
+pub fn placements(shape: &Shape<BigRational>, budget: &Budget) -> Vec<Placement> {
+	let outline: Vec<Point<BigRational>> = shape.polys.iter().filter(|poly| !poly.is_hole())
+		.flat_map(|poly| poly.points.iter().cloned()).collect();
+	if outline.is_empty() {
+		return Vec::new();
+	}
+	let hull = convex_hull(&outline);
+
+	let mut squares = Vec::new();
+	if let Some(fit) = place_square(&hull) {
+		squares.push(fit);
+	}
+	let mut min = outline[0].clone();
+	for p in outline.iter() {
+		if p.x < min.x { min.x = p.x.clone(); }
+		if p.y < min.y { min.y = p.y.clone(); }
+	}
+	let (zero, one) = (BigRational::zero(), BigRational::one());
+	squares.push(square(&min, &Point{x: one.clone(), y: zero.clone()}, &Point{x: zero, y: one}));
+
+	let mut edges = hull.edges();
+	for poly in shape.polys.iter() {
+		edges.extend(poly.edges());
+	}
+	for edge in edges.iter() {
+		let u = match rational_direction(&(&edge.p2 - &edge.p1)) {
+			Some(u) => u,
+			None => continue
+		};
+		let back = Point{x: -u.x.clone(), y: -u.y.clone()};
+		for &(ref o, ref dir) in [(&edge.p1, &u), (&edge.p2, &back)].iter() {
+			let perp = Point{x: -dir.y.clone(), y: dir.x.clone()};
+			let other = Point{x: dir.y.clone(), y: -dir.x.clone()};
+			squares.push(square(o, dir, &perp));
+			squares.push(square(o, dir, &other));
+		}
+	}
+
+	// the unit square with its axes swapped, for the mirror images
+	let swap = Matrix33::new(
+		(BigRational::zero(), BigRational::one(), BigRational::zero()),
+		(BigRational::one(), BigRational::zero(), BigRational::zero()),
+		(BigRational::zero(), BigRational::zero(), BigRational::one())
+	);
+	let mut seen = BTreeSet::new();
+	let mut found = Vec::new();
+	for sq in squares {
+		if !found.is_empty() && budget.expired() {
+			break;
+		}
+		let mut key = sq.points.clone();
+		key.sort();
+		if !seen.insert(key) {
+			continue;
+		}
+		let score = sampled_resemblance(shape, &vec![sq.clone()], SCORING_SAMPLES);
+		let mirror = Polygon::with_transform(sq.points.clone(), swap.clone() * sq.transform.clone());
+		found.push(Placement{square: sq, score: score});
+		found.push(Placement{square: mirror, score: score});
+	}
+	found.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+	found
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[test]
+	fn test_rational_direction() {
+		let u = rational_direction(&p(rat(3, 1), rat(4, 1))).unwrap();
+		assert_eq!(p(rat(3, 5), rat(4, 5)), u);
+
+		// no rational unit vector points exactly along (1, 1), but we get close
+		let u = rational_direction(&p(rat(-1, 1), rat(-1, 1))).unwrap();
+		assert_eq!(rat(1, 1), u.dot(u.clone()));
+		assert!((u.x.to_f64() + 0.5f64.sqrt()).abs() < 1e-5);
+		assert!((u.y.to_f64() + 0.5f64.sqrt()).abs() < 1e-5);
+	}
+
+	#[test]
+	fn test_placements() {
+		// a tilted square of paper, exactly where the best placement should be
+		let tilted = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(3, 5), rat(4, 5)), p(rat(-1, 5), rat(7, 5)), p(rat(-4, 5), rat(3, 5))]);
+		let found = placements(&Shape::new(vec![tilted.clone()]), &Budget::unlimited());
+		assert_eq!(1.0, found[0].score);
+		assert_eq!(1.0, found[1].score);
+		assert!((found[0].square.transform.det() < rat(0, 1)) != (found[1].square.transform.det() < rat(0, 1)));
+		for placement in found.iter().take(2) {
+			let sq = &placement.square;
+			assert!(sq.signed_area() > rat(0, 1));
+			for pt in sq.points.iter() {
+				assert!(tilted.contains(pt));
+			}
+			// back at the source, the corners are the unit square's
+			for pt in sq.source_poly().points.iter() {
+				assert!((pt.x == rat(0, 1) || pt.x == rat(1, 1)) && (pt.y == rat(0, 1) || pt.y == rat(1, 1)));
+			}
+		}
+
+		// out of time there's still the first square and its mirror image
+		let budget = Budget::unlimited();
+		budget.cancel();
+		let hurried = placements(&Shape::new(vec![tilted]), &budget);
+		assert_eq!(2, hurried.len());
+		assert_eq!(1.0, hurried[0].score);
+	}
+}
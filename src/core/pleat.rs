@@ -0,0 +1,97 @@
+// Accordion folding: pleats the paper back and forth along parallel lines until
+// it fits within a band, eg. to make a long thin strip.
+use super::*;
+
+// The band lies between `line` and the same line shifted by `offset`. Any
+// rational direction and offset will do, every fold stays exact. Paper sticking
+// out past either edge of the band is folded back over that edge, one fold at
+// a time, so each strip of paper is only folded as often as it has to be.
+// Returns the paper and whether it now fits, which it may not if max_folds
+// or the budget runs out first.
+pub fn pleat<N: Num>(state: Vec<Polygon<N>>, line: &Line<N>, offset: &Point<N>, max_folds: usize, budget: &Budget) -> (Vec<Polygon<N>>, bool) {
+	let (a, b) = (line.p1.clone(), line.p2.clone());
+	let (c, d) = (&line.p1 + offset, &line.p2 + offset);
+	// fold_over flips whatever is on the right of each line, so point them so
+	// that the outside of the band is on their right
+	let (near, far) = if side(line, &c) > N::zero() {
+		(Line::new(a, b), Line::new(d, c))
+	} else {
+		(Line::new(b, a), Line::new(c, d))
+	};
+	if side(&near, &far.p1) == N::zero() {
+		// the band has no width
+		return (state, false);
+	}
+	let outside = |state: &Vec<Polygon<N>>, edge: &Line<N>| {
+		state.iter().any(|poly| poly.points.iter().any(|p| side(edge, p) < N::zero()))
+	};
+	let mut state = state;
+	for _ in 0..max_folds {
+		if budget.expired() {
+			break;
+		}
+		if outside(&state, &far) {
+			state = fold_over(&state, &far);
+		} else if outside(&state, &near) {
+			state = fold_over(&state, &near);
+		} else {
+			return (state, true);
+		}
+	}
+	let fits = !outside(&state, &far) && !outside(&state, &near);
+	(state, fits)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn unit() -> Vec<Polygon<BigRational>> {
+		vec![Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 1), rat(0, 1)), p(rat(1, 1), rat(1, 1)), p(rat(0, 1), rat(1, 1))])]
+	}
+
+	fn source_area(state: &Vec<Polygon<BigRational>>) -> BigRational {
+		state.iter().fold(rat(0, 1), |area, poly| area + poly.source_poly().signed_area().abs())
+	}
+
+	#[test]
+	fn test_pleat_axis() {
+		// a third of the width takes two folds and leaves three strips
+		let line = Line::new(p(rat(0, 1), rat(0, 1)), p(rat(0, 1), rat(1, 1)));
+		let (state, fits) = pleat(unit(), &line, &p(rat(1, 3), rat(0, 1)), 10, &Budget::unlimited());
+		assert!(fits);
+		assert_eq!(3, state.len());
+		for poly in state.iter() {
+			assert!(poly.points.iter().all(|pt| pt.x >= rat(0, 1) && pt.x <= rat(1, 3)));
+		}
+		assert_eq!(rat(1, 1), source_area(&state));
+
+		// not enough folds
+		let (_, fits) = pleat(unit(), &line, &p(rat(1, 3), rat(0, 1)), 1, &Budget::unlimited());
+		assert!(!fits);
+
+		// nor any time to fold in
+		let budget = Budget::unlimited();
+		budget.cancel();
+		let (state, fits) = pleat(unit(), &line, &p(rat(1, 3), rat(0, 1)), 10, &budget);
+		assert!(!fits);
+		assert_eq!(1, state.len());
+	}
+
+	#[test]
+	fn test_pleat_rotated() {
+		// a band half a unit wide running along (4, 3), through the middle
+		let line = Line::new(p(rat(0, 1), rat(1, 2)), p(rat(4, 1), rat(7, 2)));
+		let offset = p(rat(3, 10), rat(-2, 5));
+		let (state, fits) = pleat(unit(), &line, &offset, 10, &Budget::unlimited());
+		assert!(fits);
+		let far = Line::new(&line.p1 + &offset, &line.p2 + &offset);
+		for poly in state.iter() {
+			for pt in poly.points.iter() {
+				assert!(side(&line, pt) <= rat(0, 1) && side(&far, pt) >= rat(0, 1));
+			}
+		}
+		assert_eq!(rat(1, 1), source_area(&state));
+	}
+}
@@ -0,0 +1,98 @@
+// Local estimate of the contest's resemblance score: the area where the
+// silhouette and the folded paper overlap, over the area covered by either.
+use super::*;
+
+// grid samples along each side of the bounding box
+const SAMPLES: usize = 160;
+
+fn to_f64<N: Num>(poly: &Polygon<N>, origin: &Point<N>) -> Polygon<f64> {
+	Polygon::new(poly.points.iter().map(|p| (p - origin).to_f64()).collect())
+}
+
+// Samples the bounding box of both on a regular grid. Accurate to a percent
+// or so, but only ever 1.0 when the two match exactly.
+pub fn resemblance<N: Num>(shape: &Shape<N>, facets: &Vec<Polygon<N>>) -> f64 {
+	sampled_resemblance(shape, facets, SAMPLES)
+}
+
+// Same again on a coarser or finer grid, eg. for quickly ranking candidates
+pub fn sampled_resemblance<N: Num>(shape: &Shape<N>, facets: &Vec<Polygon<N>>, samples: usize) -> f64 {
+	// work relative to a point nearby, floats lose huge coordinates entirely
+	let origin = match shape.polys.iter().chain(facets.iter()).flat_map(|poly| poly.points.iter()).next() {
+		Some(p) => p.clone(),
+		None => return 0.0
+	};
+	let sampled_shape = Shape::new(shape.polys.iter().map(|poly| to_f64(poly, &origin)).collect());
+	let sampled_facets: Vec<Polygon<f64>> = facets.iter().map(|poly| to_f64(poly, &origin)).collect();
+	let mut points = sampled_shape.polys.iter().chain(sampled_facets.iter()).flat_map(|poly| poly.points.iter());
+	let first = match points.next() {
+		Some(p) => p.clone(),
+		None => return 0.0
+	};
+	let (mut min, mut max) = (first.clone(), first);
+	for p in points {
+		min.x = min.x.min(p.x);
+		min.y = min.y.min(p.y);
+		max.x = max.x.max(p.x);
+		max.y = max.y.max(p.y);
+	}
+	let (dx, dy) = ((max.x - min.x) / samples as f64, (max.y - min.y) / samples as f64);
+	let (mut both, mut either) = (0, 0);
+	for i in 0..samples {
+		for j in 0..samples {
+			let p = Point{x: min.x + (i as f64 + 0.5) * dx, y: min.y + (j as f64 + 0.5) * dy};
+			let in_shape = sampled_shape.contains(&p);
+			let in_paper = sampled_facets.iter().any(|f| f.inside(&p));
+			if in_shape && in_paper {
+				both += 1;
+			}
+			if in_shape || in_paper {
+				either += 1;
+			}
+		}
+	}
+	if either == 0 {
+		return 0.0;
+	}
+	if both == either && !matches_exactly(shape, facets) {
+		// the samples all missed the difference, so count it as one of them
+		return 1.0 - 1.0 / (samples * samples) as f64;
+	}
+	both as f64 / either as f64
+}
+
+// Whether the paper covers the silhouette and nothing else. Every face of the
+// arrangement of both outlines must be inside both or outside both.
+pub fn matches_exactly<N: Num>(shape: &Shape<N>, facets: &Vec<Polygon<N>>) -> bool {
+	let lines: Vec<Line<N>> = shape.polys.iter().chain(facets.iter()).flat_map(|poly| poly.edges()).collect();
+	faces(&lines).iter().all(|face| match face.interior_point() {
+		Some(p) => shape.contains(&p) == facets.iter().any(|f| f.inside(&p)),
+		None => true
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[test]
+	fn test_resemblance() {
+		let square = Polygon::new(vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)]);
+		let shape = Shape::new(vec![square.clone()]);
+		assert_eq!(1.0, resemblance(&shape, &vec![square]));
+
+		// left half of the square: half the union is covered by both
+		let half = Polygon::new(vec![p(0.0, 0.0), p(0.5, 0.0), p(0.5, 1.0), p(0.0, 1.0)]);
+		assert!((resemblance(&shape, &vec![half]) - 0.5).abs() < 0.01);
+
+		// a sliver too thin for any sample to land in
+		let sliver = Polygon::new(vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0), p(0.0, 0.5001), p(-0.0001, 0.5), p(0.0, 0.4999)]);
+		assert!(resemblance(&shape, &vec![sliver.clone()]) < 1.0);
+		assert!(resemblance(&shape, &vec![sliver]) > 0.99);
+
+		// disjoint
+		let away = Polygon::new(vec![p(2.0, 0.0), p(3.0, 0.0), p(3.0, 1.0), p(2.0, 1.0)]);
+		assert_eq!(0.0, resemblance(&shape, &vec![away]));
+	}
+}
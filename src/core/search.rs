@@ -0,0 +1,242 @@
+// Beam search over sequences of folds. Each step tries folding the current
+// paper along every candidate line, in both directions, and keeps the best
+// few states by (quickly estimated) resemblance.
+use super::*;
+
+use num::BigInt;
+use num::rational::BigRational;
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+// grid used to rank states while searching, the final answer is scored properly
+const RANKING_SAMPLES: usize = 48;
+// fold lines tried per state
+const MAX_CANDIDATES: usize = 80;
+
+#[derive(Debug,Clone)]
+pub struct BeamOptions {
+	// most folds in a sequence
+	pub depth: usize,
+	// states kept after each step
+	pub width: usize,
+}
+
+impl Default for BeamOptions {
+	fn default() -> BeamOptions {
+		BeamOptions{depth: 4, width: 8}
+	}
+}
+
+#[derive(Clone)]
+struct Node {
+	folds: Vec<Line<BigRational>>,
+	state: Vec<Polygon<BigRational>>,
+	score: f64,
+	size: usize,
+}
+
+// The infinite line through l as ax + by = c, scaled so the first of a and b
+// that isn't zero is one. Equal keys mean the same line.
+fn line_key(l: &Line<BigRational>) -> Option<(BigRational, BigRational, BigRational)> {
+	let a = l.p2.y.clone() - l.p1.y.clone();
+	let b = l.p1.x.clone() - l.p2.x.clone();
+	let c = a.clone() * l.p1.x.clone() + b.clone() * l.p1.y.clone();
+	let scale = if a != BigRational::zero() { a.clone() } else if b != BigRational::zero() { b.clone() } else { return None };
+	Some((a / scale.clone(), b / scale.clone(), c / scale))
+}
+
+// Lines worth folding along: the silhouette's hull and edges, the skeleton,
+// and lines that would fold a stray corner of the paper onto the silhouette
+fn candidates(problem: &Problem, state: &Vec<Polygon<BigRational>>) -> Vec<Line<BigRational>> {
+	let outline: Vec<&Polygon<BigRational>> = problem.shape.polys.iter().filter(|poly| !poly.is_hole()).collect();
+	let mut targets: Vec<Point<BigRational>> = outline.iter().flat_map(|poly| poly.points.iter().cloned()).collect();
+	let hull = convex_hull(&targets);
+	targets.sort();
+	targets.dedup_by(|a, b| (*a).cmp(b) == Ordering::Equal);
+
+	let mut lines = hull.edges();
+	for poly in problem.shape.polys.iter() {
+		lines.extend(poly.edges());
+	}
+	let mut corners: Vec<Point<BigRational>> = state.iter().flat_map(|poly| poly.points.iter().cloned())
+		.filter(|p| !problem.shape.contains(p)).collect();
+	corners.sort();
+	corners.dedup_by(|a, b| (*a).cmp(b) == Ordering::Equal);
+	let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+	for c in corners.iter() {
+		for t in targets.iter() {
+			let d = t - c;
+			let mid = c + d.scale(half.clone());
+			let perp = Point{x: -d.y, y: d.x};
+			lines.push(Line::new(mid.clone(), &mid + &perp));
+		}
+	}
+	lines.extend(problem.skeleton.lines.iter().cloned());
+
+	let mut seen = BTreeSet::new();
+	let mut picked = Vec::new();
+	for line in lines {
+		if picked.len() == MAX_CANDIDATES {
+			break;
+		}
+		let key = match line_key(&line) {
+			Some(key) => key,
+			None => continue
+		};
+		let cuts = state.iter().any(|poly| poly.points.iter().any(|p| side(&line, p) > BigRational::zero())) &&
+			state.iter().any(|poly| poly.points.iter().any(|p| side(&line, p) < BigRational::zero()));
+		if cuts && seen.insert(key) {
+			picked.push(line);
+		}
+	}
+	picked
+}
+
+// Rough size of the written solution, for breaking ties and pruning
+fn size_estimate(state: &Vec<Polygon<BigRational>>) -> usize {
+	state.iter().map(|poly| {
+		let digits: usize = poly.points.iter().map(|p| format!("{}{}", p.x, p.y).len() + 1).sum();
+		2 * digits + poly.points.len() * 2
+	}).sum()
+}
+
+// The corners of the facets, each with which way up its facet is, so a mirror
+// image isn't taken for the state it mirrors
+fn state_key(state: &Vec<Polygon<BigRational>>) -> Vec<(Point<BigRational>, bool)> {
+	let mut points: Vec<(Point<BigRational>, bool)> = state.iter().flat_map(|poly| {
+		let flipped = poly.transform.det() < BigRational::zero();
+		poly.points.iter().map(move |p| (p.clone(), flipped))
+	}).collect();
+	points.sort();
+	points
+}
+
+fn ranked(a: &Node, b: &Node) -> Ordering {
+	b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal).then(a.size.cmp(&b.size))
+}
+
+// Searches fold sequences starting from each of the placed squares. Returns
+// the folds made and the solution for the best state found, which is the best
+// so far if the budget runs out.
+pub fn beam_search(problem: &Problem, squares: Vec<Polygon<BigRational>>, options: &BeamOptions, budget: &Budget) -> Option<(Vec<Line<BigRational>>, Solution)> {
+	let mut beam: Vec<Node> = squares.into_iter().map(|square| {
+		let state = vec![square];
+		Node{folds: Vec::new(), score: sampled_resemblance(&problem.shape, &state, RANKING_SAMPLES), size: size_estimate(&state), state: state}
+	}).collect();
+	beam.sort_by(ranked);
+	let mut best = match beam.first() {
+		Some(node) => node.clone(),
+		None => return None
+	};
+	let mut seen = BTreeSet::new();
+	for _ in 0..options.depth {
+		let mut next = Vec::new();
+		for node in beam.iter() {
+			for line in candidates(problem, &node.state) {
+				if budget.expired() {
+					break;
+				}
+				let reversed = Line::new(line.p2.clone(), line.p1.clone());
+				for fold in vec![line, reversed] {
+					if !budget.step() {
+						break;
+					}
+					let state = fold_over(&node.state, &fold);
+					let size = size_estimate(&state);
+					if size > MAX_SOLUTION_SIZE || !seen.insert(state_key(&state)) {
+						continue;
+					}
+					let mut folds = node.folds.clone();
+					folds.push(fold);
+					let score = sampled_resemblance(&problem.shape, &state, RANKING_SAMPLES);
+					next.push(Node{folds: folds, state: state, score: score, size: size});
+				}
+			}
+		}
+		if next.is_empty() {
+			break;
+		}
+		next.sort_by(ranked);
+		next.truncate(options.width);
+		if ranked(&next[0], &best) == Ordering::Less {
+			best = next[0].clone();
+		}
+		beam = next;
+		if budget.expired() || best.score == 1.0 {
+			break;
+		}
+	}
+	let Node{folds, state, ..} = best;
+	checked("beam", problem, state, BigInt::from(1)).map(|solution| (folds, solution))
+}
+
+pub struct BeamStrategy {
+	pub options: BeamOptions,
+}
+
+impl Strategy for BeamStrategy {
+	fn name(&self) -> &'static str { "beam" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		problem.shape.polys.iter().any(|poly| !poly.is_hole())
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		// mirror images fold the same but lay the source out differently, so
+		// they compete too
+		let squares = placements(&problem.shape, budget).into_iter().take(self.options.width).map(|p| p.square).collect();
+		beam_search(problem, squares, &self.options, budget).map(|(folds, solution)| {
+			println!("beam: {} folds", folds.len());
+			for fold in folds.iter() {
+				println!("   fold along {}", fold);
+			}
+			solution
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn problem(polys: Vec<Polygon<BigRational>>) -> Problem {
+		Problem{shape: Shape::new(polys), skeleton: Skeleton::new(Vec::new())}
+	}
+
+	#[test]
+	fn test_beam_search() {
+		// the bottom half of the paper, off to one side: one fold
+		let strip = Polygon::new(vec![p(rat(2, 1), rat(0, 1)), p(rat(3, 1), rat(0, 1)), p(rat(3, 1), rat(1, 2)), p(rat(2, 1), rat(1, 2))]);
+		let problem = problem(vec![strip]);
+		let squares = placements(&problem.shape, &Budget::unlimited()).into_iter().map(|p| p.square).take(4).collect();
+		let (folds, solution) = beam_search(&problem, squares, &BeamOptions::default(), &Budget::unlimited()).unwrap();
+		assert_eq!(1, folds.len());
+		assert_eq!(1.0, solution.resemblance);
+	}
+
+	#[test]
+	fn test_state_key() {
+		// a placement and its mirror image cover the same ground, but they're
+		// different states
+		let found = placements(&Shape::new(vec![unit_square()]), &Budget::unlimited());
+		let flipped = |p: &Placement| p.square.transform.det() < rat(0, 1);
+		let twin = found.iter().find(|p| flipped(p) != flipped(&found[0]) && p.square.points == found[0].square.points).unwrap();
+		let (a, b) = (state_key(&vec![found[0].square.clone()]), state_key(&vec![twin.square.clone()]));
+		assert!(a != b);
+		assert_eq!(a.iter().map(|k| k.0.clone()).collect::<Vec<_>>(), b.iter().map(|k| k.0.clone()).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_beam_search_corners() {
+		// a quarter of the paper takes two folds
+		let quarter = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 2), rat(0, 1)), p(rat(1, 2), rat(1, 2)), p(rat(0, 1), rat(1, 2))]);
+		let problem = problem(vec![quarter]);
+		let options = BeamOptions{depth: 3, width: 4};
+		let squares = placements(&problem.shape, &Budget::unlimited()).into_iter().map(|p| p.square).take(options.width).collect();
+		let (folds, solution) = beam_search(&problem, squares, &options, &Budget::unlimited()).unwrap();
+		assert_eq!(2, folds.len());
+		assert_eq!(1.0, solution.resemblance);
+	}
+}
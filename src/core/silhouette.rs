@@ -0,0 +1,191 @@
+// What the judges see of a folded state: the outline of all the facets laid
+// on top of each other, holes and all, and the creases between them.
+use super::*;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+// Drops vertices that lie on a straight run of the outline
+fn merge_collinear<N: Num>(points: Vec<Point<N>>) -> Vec<Point<N>> {
+	let mut points = points;
+	loop {
+		let n = points.len();
+		if n <= 3 {
+			return points;
+		}
+		match (0..n).find(|&i| side(&Line::new(points[(i+n-1)%n].clone(), points[i].clone()), &points[(i+1)%n]) == N::zero()) {
+			Some(i) => { points.remove(i); },
+			None => return points
+		}
+	}
+}
+
+// The union of the facets as the contest writes a silhouette: outlines
+// counter-clockwise, holes clockwise, biggest first. Facets may overlap and
+// face either way. Where two outlines touch at a corner they're kept apart,
+// so every polygon is simple.
+pub fn silhouette<N: Num>(facets: &Vec<Polygon<N>>) -> Shape<N> {
+	let lines: Vec<Line<N>> = facets.iter().flat_map(|f| f.edges()).collect();
+	// edges of the covered faces of the arrangement, each face on its left
+	let mut covered = BTreeMap::new();
+	for face in faces(&lines) {
+		if face.interior_point().map_or(false, |p| facets.iter().any(|f| f.inside(&p))) {
+			for edge in face.edges() {
+				covered.insert((edge.p1, edge.p2), ());
+			}
+		}
+	}
+	// the boundary is where the face across has no paper
+	let mut out: BTreeMap<Point<N>, Vec<Point<N>>> = BTreeMap::new();
+	for &(ref p, ref q) in covered.keys() {
+		if !covered.contains_key(&(q.clone(), p.clone())) {
+			out.entry(p.clone()).or_insert(Vec::new()).push(q.clone());
+		}
+	}
+
+	let mut polys = Vec::new();
+	while let Some(start) = out.keys().next().cloned() {
+		let mut points = vec![start.clone()];
+		let mut prev = start.clone();
+		let mut p = out.get_mut(&start).unwrap().pop().unwrap();
+		while p != start {
+			// where outlines touch there's a choice of ways on, and taking the
+			// one immediately clockwise of the way we came keeps to this loop,
+			// just as walking the faces does
+			let back = &prev - &p;
+			let q = {
+				let ways = match out.get_mut(&p) {
+					Some(ways) => ways,
+					None => break
+				};
+				let directions: Vec<Point<N>> = ways.iter().map(|q| q - &p).collect();
+				match clockwise_of(&back, &directions) {
+					Some(i) => ways.remove(i),
+					None => break
+				}
+			};
+			points.push(p.clone());
+			prev = p;
+			p = q;
+		}
+		out.retain(|_, ways| !ways.is_empty());
+		let points = merge_collinear(points);
+		if points.len() >= 3 {
+			polys.push(Polygon::new(points));
+		}
+	}
+	polys.sort_by(|a, b| b.signed_area().partial_cmp(&a.signed_area()).unwrap_or(Ordering::Equal));
+	Shape::new(polys)
+}
+
+// Every crease and bit of the outline once, as the skeleton of the problem the
+// facets set. Segments carrying straight on through a vertex that joins
+// nothing else are merged into one.
+pub fn creases<N: Num>(facets: &Vec<Polygon<N>>) -> Skeleton<N> {
+	let lines: Vec<Line<N>> = facets.iter().flat_map(|f| f.edges()).collect();
+	let (points, edges) = planar_graph(&lines);
+	let mut around = vec![Vec::new(); points.len()];
+	for (i, &(a, b)) in edges.iter().enumerate() {
+		around[a].push(i);
+		around[b].push(i);
+	}
+	let other = |e: usize, v: usize| if edges[e].0 == v { edges[e].1 } else { edges[e].0 };
+	let straight_on = |e: usize, v: usize| {
+		if around[v].len() != 2 {
+			return None;
+		}
+		let f = if around[v][0] == e { around[v][1] } else { around[v][0] };
+		let turn = side(&Line::new(points[other(e, v)].clone(), points[v].clone()), &points[other(f, v)]);
+		if turn == N::zero() { Some(f) } else { None }
+	};
+	let mut used = vec![false; edges.len()];
+	let mut merged = Vec::new();
+	for e in 0..edges.len() {
+		if used[e] {
+			continue;
+		}
+		used[e] = true;
+		let mut ends = [edges[e].0, edges[e].1];
+		for end in ends.iter_mut() {
+			let mut cur = e;
+			while let Some(f) = straight_on(cur, *end) {
+				if used[f] {
+					break;
+				}
+				used[f] = true;
+				*end = other(f, *end);
+				cur = f;
+			}
+		}
+		merged.push(Line::new(points[ends[0]].clone(), points[ends[1]].clone()));
+	}
+	Skeleton::new(merged)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn square(x: i64, y: i64, size: i64) -> Polygon<BigRational> {
+		Polygon::new(vec![p(rat(x, 1), rat(y, 1)), p(rat(x + size, 1), rat(y, 1)), p(rat(x + size, 1), rat(y + size, 1)), p(rat(x, 1), rat(y + size, 1))])
+	}
+
+	fn reversed(poly: Polygon<BigRational>) -> Polygon<BigRational> {
+		let mut points = poly.points;
+		points.reverse();
+		Polygon::new(points)
+	}
+
+	#[test]
+	fn test_silhouette() {
+		// overlapping squares, one of them facing the other way, make an L-ish
+		// octagon with no trace of the overlap
+		let shape = silhouette(&vec![square(0, 0, 2), reversed(square(1, 1, 2))]);
+		assert_eq!(1, shape.polys.len());
+		assert_eq!(8, shape.polys[0].points.len());
+		assert_eq!(rat(7, 1), shape.polys[0].signed_area());
+
+		// a ring of four strips round a hole
+		let ring = vec![
+			Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(3, 1), rat(0, 1)), p(rat(3, 1), rat(1, 1)), p(rat(0, 1), rat(1, 1))]),
+			Polygon::new(vec![p(rat(2, 1), rat(0, 1)), p(rat(3, 1), rat(0, 1)), p(rat(3, 1), rat(3, 1)), p(rat(2, 1), rat(3, 1))]),
+			Polygon::new(vec![p(rat(0, 1), rat(2, 1)), p(rat(3, 1), rat(2, 1)), p(rat(3, 1), rat(3, 1)), p(rat(0, 1), rat(3, 1))]),
+			Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 1), rat(0, 1)), p(rat(1, 1), rat(3, 1)), p(rat(0, 1), rat(3, 1))]),
+		];
+		let shape = silhouette(&ring);
+		assert_eq!(2, shape.polys.len());
+		assert_eq!((4, rat(9, 1)), (shape.polys[0].points.len(), shape.polys[0].signed_area()));
+		assert_eq!((4, rat(-1, 1)), (shape.polys[1].points.len(), shape.polys[1].signed_area()));
+		assert!(shape.contains(&p(rat(1, 2), rat(3, 2))));
+		assert!(!shape.contains(&p(rat(3, 2), rat(3, 2))));
+
+		// squares touching at a corner stay two squares
+		let shape = silhouette(&vec![square(0, 0, 1), square(1, 1, 1)]);
+		assert_eq!(2, shape.polys.len());
+		assert!(shape.polys.iter().all(|poly| poly.points.len() == 4 && poly.signed_area() == rat(1, 1)));
+	}
+
+	#[test]
+	fn test_silhouette_near_parallel() {
+		// two long slivers pointing left from the same corner, too close to
+		// parallel for atan2 to tell apart
+		let far = rat(-100000000000000000, 1);
+		let origin = p(rat(0, 1), rat(0, 1));
+		let sliver = |k: i64| Polygon::new(vec![origin.clone(), p(far.clone(), rat(k + 1, 1)), p(far.clone(), rat(k, 1))]);
+		let shape = silhouette(&vec![sliver(0), sliver(2)]);
+		assert_eq!(2, shape.polys.len());
+		assert!(shape.polys.iter().all(|poly| poly.points.len() == 3 && poly.signed_area() == -far.clone() * rat(1, 2)));
+	}
+
+	#[test]
+	fn test_creases() {
+		// the unit square folded in half down the middle, both layers
+		let half = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 2), rat(0, 1)), p(rat(1, 2), rat(1, 1)), p(rat(0, 1), rat(1, 1))]);
+		assert_eq!(4, creases(&vec![half.clone(), reversed(half.clone())]).lines.len());
+		// side by side, the shared edge is a crease and the long sides merge
+		let right = Polygon::new(vec![p(rat(1, 2), rat(0, 1)), p(rat(1, 1), rat(0, 1)), p(rat(1, 1), rat(1, 1)), p(rat(1, 2), rat(1, 1))]);
+		let skeleton = creases(&vec![half, right]);
+		assert_eq!(7, skeleton.lines.len());
+	}
+}
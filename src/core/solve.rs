@@ -1,5 +1,15 @@
 use super::*;
 use super::super::matrix::Matrix33;
+use ::parse::parse_solution;
+use ::write::from_polys;
+
+use num::BigInt;
+use num::integer::lcm;
+use num::rational::BigRational;
+
+use std::sync::{Arc,mpsc};
+use std::thread;
+use std::time::Duration;
 
 // l0.p2 and l1.p1 are the same since this is where the lines join
 // l0 and l1 must be perpendicular
@@ -48,16 +58,439 @@ pub fn get_next_edge_to_fold<N: Num>(base: Polygon<N>, silhouette: Polygon<N>) -
 	return longest;
 }
 
-pub fn fold_origami<N: Num>(state: &Vec<(Polygon<N>)>, vertex1: &Point<N>, vertex2: &Point<N>, anchor: &Point<N>) -> Vec<Polygon<N>>{
+// Folds the paper along the infinite line through `line`. Everything to the
+// right of line.p1 -> line.p2 is flipped over onto the left hand side.
+// Facets must be convex, which holds for anything folded from the unit square.
+pub fn fold_over<N: Num>(state: &Vec<Polygon<N>>, line: &Line<N>) -> Vec<Polygon<N>> {
+	let mut folded = Vec::new();
+	for poly in state {
+		let (left, right) = split_convex(poly, line);
+		if let Some(l) = left {
+			folded.push(l);
+		}
+		if let Some(r) = right {
+			folded.push(flip_polygon(&r, &line.p1, &line.p2));
+		}
+	}
+	folded
+}
+
+// Places the unit square so that it covers the given (convex) hull. The axis
+// aligned placement is tried first, then the direction of each hull edge with
+// a rational length (those are the only rotations we can represent exactly).
+pub fn place_square<N: Num>(hull: &Polygon<N>) -> Option<Polygon<N>> {
+	let mut frames = vec![(N::zero(), N::one())];
+	for edge in hull.edges() {
+		let d = &edge.p2 - &edge.p1;
+		if let Some(len) = d.dot(d.clone()).sqrt() {
+			if len != N::zero() {
+				frames.push((d.y.clone() / len.clone(), d.x.clone() / len));
+			}
+		}
+	}
+	for (sin, cos) in frames {
+		let to_frame = Matrix33::rotate(-sin.clone(), cos.clone());
+		let pts: Vec<Point<N>> = hull.points.iter().map(|p| to_frame.transform(p.clone())).collect();
+		let (mut min, mut max) = (pts[0].clone(), pts[0].clone());
+		for p in pts.iter() {
+			if p.x < min.x { min.x = p.x.clone(); }
+			if p.y < min.y { min.y = p.y.clone(); }
+			if p.x > max.x { max.x = p.x.clone(); }
+			if p.y > max.y { max.y = p.y.clone(); }
+		}
+		if max.x.clone() - min.x.clone() <= N::one() && max.y.clone() - min.y.clone() <= N::one() {
+			let transform = Matrix33::translate(min.x, min.y).then_rotate(sin, cos);
+			let unit = vec![
+				Point{x: N::zero(), y: N::zero()}, Point{x: N::one(), y: N::zero()},
+				Point{x: N::one(), y: N::one()}, Point{x: N::zero(), y: N::one()}
+			];
+			let points = unit.into_iter().map(|p| transform.transform(p)).collect();
+			return Some(Polygon::with_transform(points, transform));
+		}
+	}
+	None
+}
+
+// Wraps the unit square around the convex hull of the silhouette by folding
+// along each hull edge in turn, until no paper is left outside the hull. If
+// the budget runs out first, the paper is left as far as it got. None if the
+// square can't cover the hull, in any of the frames place_square tries.
+pub fn wrap_hull<N: Num>(shape: &Shape<N>, budget: &Budget) -> Option<Vec<Polygon<N>>> {
+	const MAX_PASSES: usize = 8;
+	let mut points = Vec::new();
+	for poly in shape.polys.iter().filter(|poly| !poly.is_hole()) {
+		points.extend(poly.points.iter().cloned());
+	}
+	let hull = convex_hull(&points);
+	if hull.points.len() < 3 {
+		return None;
+	}
+	let mut state = match place_square(&hull) {
+		Some(square) => vec![square],
+		None => return None
+	};
+	for _ in 0..MAX_PASSES {
+		let mut folded = false;
+		for edge in hull.edges() {
+			if state.iter().any(|poly| poly.points.iter().any(|p| side(&edge, p) < N::zero())) {
+				if !budget.step() {
+					return Some(state);
+				}
+				state = fold_over(&state, &edge);
+				folded = true;
+			}
+		}
+		if !folded {
+			break;
+		}
+	}
+	Some(state)
+}
+
+// Folds every layer of the paper. Half a fold is no use to anyone, so if the
+// budget runs out part way the paper comes back as it was.
+pub fn fold_origami<N: Num>(state: &Vec<(Polygon<N>)>, vertex1: &Point<N>, vertex2: &Point<N>, anchor: &Point<N>, budget: &Budget) -> Vec<Polygon<N>>{
 	let mut folded = Vec::new();
 
 	for poly in state {
-		folded.append(&mut fold_polygon(&poly, &vertex1, &vertex2, &anchor));
+		match fold_polygon(&poly, &vertex1, &vertex2, &anchor, budget) {
+			Some(mut polys) => folded.append(&mut polys),
+			None => return state.clone()
+		}
 	}
 	folded
 }
 
 
+#[derive(Debug)]
+pub enum SolveError {
+	// no strategy came up with anything
+	Unsolved,
+	// an attempt broke the rules, and nothing better came along
+	Invalid(String),
+}
+
+pub struct SolveOptions {
+	// names of the strategies to try, or all of them when empty
+	pub strategies: Vec<String>,
+	// search limit for the skeleton unfolder
+	pub unfold_steps: usize,
+	pub beam: BeamOptions,
+	// run the strategies side by side instead of one after another
+	pub parallel: bool,
+	pub time_limit: Option<Duration>,
+	// folds, cuts and search steps allowed across all the strategies
+	pub step_limit: Option<usize>,
+}
+
+impl Default for SolveOptions {
+	fn default() -> SolveOptions {
+		SolveOptions{strategies: Vec::new(), unfold_steps: 100000, beam: BeamOptions::default(), parallel: false, time_limit: None, step_limit: None}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Solution {
+	pub strategy: String,
+	// exactly what gets submitted
+	pub spec: String,
+	pub resemblance: f64,
+}
+
+impl Solution {
+	// Writes out the folded paper and checks the result is something the judges
+	// would accept. `base` is handed on to from_polys for snapping.
+	pub fn new(strategy: &str, shape: &Shape<BigRational>, folded: Vec<Polygon<BigRational>>, base: BigInt) -> Result<Solution, SolveError> {
+		let mut out = Vec::new();
+		try!(from_polys(&mut out, folded, base).map_err(|e| SolveError::Invalid(format!("{}", e))));
+		let spec = String::from_utf8(out).unwrap();
+		let parsed = try!(parse_solution::<BigRational, &[u8]>(spec.as_bytes()).map_err(|e| SolveError::Invalid(format!("{:?}", e))));
+		try!(parsed.validate().map_err(SolveError::Invalid));
+		let mut solution = Solution{strategy: strategy.to_string(), spec: spec, resemblance: 0.0};
+		if solution.size() > MAX_SOLUTION_SIZE {
+			return Err(SolveError::Invalid(format!("Solution size {} is over the limit of {}", solution.size(), MAX_SOLUTION_SIZE)));
+		}
+		solution.resemblance = resemblance(shape, &parsed.dst_polys());
+		Ok(solution)
+	}
+
+	// The same solution, scored against a different silhouette
+	pub fn rescored(mut self, shape: &Shape<BigRational>) -> Solution {
+		if let Ok(parsed) = parse_solution::<BigRational, &[u8]>(self.spec.as_bytes()) {
+			self.resemblance = resemblance(shape, &parsed.dst_polys());
+		}
+		self
+	}
+
+	pub fn size(&self) -> usize {
+		spec_size(&self.spec)
+	}
+}
+
+// The corner of the silhouette with the longest pair of edges
+pub fn anchor_corner<N: Num>(shape: &Shape<N>) -> Option<(Line<N>, Line<N>)> {
+	let mut anchor = None;
+	let mut longest = 0.0_f64;
+	for polygon in shape.polys.iter() {
+		for corner in polygon.corners() {
+			let length = p_distance(&corner.0.p1, &corner.0.p2).to_f64() + p_distance(&corner.1.p1, &corner.1.p2).to_f64();
+			if length > longest {
+				longest = length;
+				anchor = Some(corner);
+			}
+		}
+	}
+	anchor
+}
+
+// Lowest common multiple of 360 and every denominator in the problem. Output
+// of the approximate strategies is snapped to multiples of its reciprocal.
+pub fn problem_base(shape: &Shape<BigRational>, skel: &Skeleton<BigRational>) -> BigInt {
+	let mut base = BigInt::from(360);
+	let points = shape.polys.iter().flat_map(|poly| poly.points.iter())
+		.chain(skel.lines.iter().flat_map(|line| vec![&line.p1, &line.p2].into_iter()));
+	for point in points {
+		base = lcm(base, point.x.denom().clone());
+		base = lcm(base, point.y.denom().clone());
+	}
+	base
+}
+
+// A problem as the strategies see it
+pub struct Problem {
+	pub shape: Shape<BigRational>,
+	pub skeleton: Skeleton<BigRational>,
+}
+
+pub trait Strategy: Send + Sync {
+	fn name(&self) -> &'static str;
+
+	// Quick check for whether an attempt could get anywhere
+	fn applicable(&self, _problem: &Problem) -> bool {
+		true
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution>;
+}
+
+// Turns folded paper into a solution, reporting why not if it isn't one
+pub fn checked(strategy: &str, problem: &Problem, folded: Vec<Polygon<BigRational>>, base: BigInt) -> Option<Solution> {
+	match Solution::new(strategy, &problem.shape, folded, base) {
+		Ok(solution) => Some(solution),
+		Err(e) => {
+			println!("{}: {:?}", strategy, e);
+			None
+		}
+	}
+}
+
+pub struct UnfoldStrategy {
+	pub max_steps: usize,
+}
+
+impl Strategy for UnfoldStrategy {
+	fn name(&self) -> &'static str { "unfold" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		!problem.skeleton.lines.is_empty()
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		unfold_skeleton(&problem.shape, &problem.skeleton, self.max_steps, budget).and_then(|folded| {
+			checked(self.name(), problem, folded, BigInt::from(1))
+		})
+	}
+}
+
+pub struct HullStrategy;
+
+impl Strategy for HullStrategy {
+	fn name(&self) -> &'static str { "hull" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		problem.shape.polys.iter().any(|poly| !poly.is_hole())
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		match wrap_hull(&problem.shape, budget) {
+			Some(folded) => checked(self.name(), problem, folded, BigInt::from(1)),
+			None => {
+				// the hull is bigger than the paper, so cover what we can of
+				// its bounding box instead
+				println!("{}: the hull doesn't fit the paper, folding to the bounding box", self.name());
+				BboxStrategy.attempt(problem, budget)
+			}
+		}
+	}
+}
+
+// The original approach: put the square on the anchor corner, then fold along
+// the longest silhouette edge that cuts the paper, a couple of times over
+pub struct GreedyStrategy {
+	pub max_folds: usize,
+}
+
+impl Strategy for GreedyStrategy {
+	fn name(&self) -> &'static str { "greedy" }
+
+	fn applicable(&self, problem: &Problem) -> bool {
+		anchor_corner(&problem.shape).is_some()
+	}
+
+	fn attempt(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		let (l1, l2) = match anchor_corner(&problem.shape) {
+			Some(corner) => corner,
+			None => return None
+		};
+		// aim for the outline of the biggest piece of the silhouette
+		let silhouette = match islands(&problem.shape).into_iter().next() {
+			Some(island) => island.polys[0].clone(),
+			None => return None
+		};
+		let mut state = vec![square_from_corner(&l1, &l2)];
+		for _ in 0..self.max_folds {
+			if budget.expired() {
+				break;
+			}
+			match get_next_edge_to_fold(state[0].clone(), silhouette.clone()) {
+				Ok(fe) => {
+					let anchor = state[0].points[0].clone();
+					let folded = fold_origami(&state, &fe.p1, &fe.p2, &anchor, budget);
+					if folded.is_empty() {
+						break;
+					}
+					state = folded;
+				},
+				Err(_) => break
+			}
+		}
+		checked(self.name(), problem, state, problem_base(&problem.shape, &problem.skeleton))
+	}
+}
+
+fn better(a: &Option<Solution>, b: &Solution) -> bool {
+	match *a {
+		Some(ref a) => b.resemblance > a.resemblance,
+		None => true
+	}
+}
+
+// A set of strategies, run one after another or all at once, keeping the best
+// solution by local resemblance
+pub struct Portfolio {
+	strategies: Vec<Arc<dyn Strategy>>,
+}
+
+impl Portfolio {
+	pub fn new() -> Portfolio {
+		Portfolio{strategies: Vec::new()}
+	}
+
+	// Everything we've got, exact strategies first and the fallback last
+	pub fn standard(options: &SolveOptions) -> Portfolio {
+		Portfolio::new()
+			.add(UnfoldStrategy{max_steps: options.unfold_steps})
+			.add(HullStrategy)
+			.add(BeamStrategy{options: options.beam.clone()})
+			.add(IslandStrategy{beam: options.beam.clone()})
+			.add(GreedyStrategy{max_folds: 2})
+			.add(BboxStrategy)
+	}
+
+	pub fn add<S: Strategy + 'static>(mut self, strategy: S) -> Portfolio {
+		self.strategies.push(Arc::new(strategy));
+		self
+	}
+
+	// Keeps just the named strategies, or all of them if no names are given
+	pub fn only(mut self, names: &Vec<String>) -> Portfolio {
+		if !names.is_empty() {
+			self.strategies.retain(|s| names.iter().any(|name| name == s.name()));
+		}
+		self
+	}
+
+	pub fn names(&self) -> Vec<&'static str> {
+		self.strategies.iter().map(|s| s.name()).collect()
+	}
+
+	// Tries each strategy in turn, stopping early on a perfect solution. Once
+	// the budget is spent the rest still get a look until there's something
+	// to show, since they hand back what they've got straight away.
+	pub fn run(&self, problem: &Problem, budget: &Budget) -> Option<Solution> {
+		let mut best = None;
+		for strategy in self.strategies.iter() {
+			if budget.expired() && best.is_some() {
+				break;
+			}
+			if !strategy.applicable(problem) {
+				continue;
+			}
+			if let Some(solution) = strategy.attempt(problem, budget) {
+				println!("{}: resemblance {}, size {}", strategy.name(), solution.resemblance, solution.size());
+				if better(&best, &solution) {
+					best = Some(solution);
+				}
+			}
+			if let Some(ref b) = best {
+				if b.resemblance == 1.0 {
+					break;
+				}
+			}
+		}
+		best
+	}
+
+	// Runs every applicable strategy on its own thread. A strategy that panics
+	// just doesn't contribute, and a perfect solution calls off the rest.
+	pub fn run_parallel(&self, problem: Arc<Problem>, budget: &Budget) -> Option<Solution> {
+		let (tx, rx) = mpsc::channel();
+		let handles: Vec<_> = self.strategies.iter().filter(|s| s.applicable(&problem)).map(|strategy| {
+			let (strategy, problem, budget, tx) = (strategy.clone(), problem.clone(), budget.clone(), tx.clone());
+			thread::spawn(move || {
+				if let Some(solution) = strategy.attempt(&problem, &budget) {
+					let _ = tx.send(solution);
+				}
+			})
+		}).collect();
+		drop(tx);
+		let mut best = None;
+		for solution in rx {
+			println!("{}: resemblance {}, size {}", solution.strategy, solution.resemblance, solution.size());
+			if solution.resemblance == 1.0 {
+				budget.cancel();
+			}
+			if better(&best, &solution) {
+				best = Some(solution);
+			}
+		}
+		for handle in handles {
+			let _ = handle.join();
+		}
+		best
+	}
+}
+
+// Runs the chosen strategies and returns the valid solution with the best
+// resemblance
+pub fn solve(shape: &Shape<BigRational>, skeleton: &Skeleton<BigRational>, options: &SolveOptions) -> Result<Solution, SolveError> {
+	let portfolio = Portfolio::standard(options).only(&options.strategies);
+	println!("Solving with {}", portfolio.names().join(", "));
+	let problem = Problem{shape: shape.clone(), skeleton: skeleton.clone()};
+	let mut budget = match options.time_limit {
+		Some(limit) => Budget::with_limit(limit),
+		None => Budget::unlimited()
+	};
+	if let Some(steps) = options.step_limit {
+		budget = budget.limit_steps(steps);
+	}
+	let best = if options.parallel {
+		portfolio.run_parallel(Arc::new(problem), &budget)
+	} else {
+		portfolio.run(&problem, &budget)
+	};
+	best.ok_or(SolveError::Unsolved)
+}
 
 #[cfg(test)]
 mod tests {
@@ -127,7 +560,7 @@ mod tests {
 		// fold top-left corner onto bottom-right
 		let fold1 = (p(0.0, 0.0), p(1.0, 1.0));
 		//let fold1 = (p(0.25, 0.25), p(0.75, 0.75));
-		let polys1 = fold_origami(&base, &fold1.0, &fold1.1, &p(1.0, 0.0));
+		let polys1 = fold_origami(&base, &fold1.0, &fold1.1, &p(1.0, 0.0), &Budget::unlimited());
 		printpolys(&polys1);
 		for pt in vec![p(0.0, 0.0), p(1.0, 1.0), p(1.0, 0.0)] {
 			assert!(polys1[0].points.contains(&pt));
@@ -137,8 +570,134 @@ mod tests {
 
 		// fold top-right corner directly downwards
 		let fold2 = (p(0.0, 0.25), p(1.0, 0.25));
-		let polys2 = fold_origami(&polys1, &fold2.0, &fold2.1, &p(0.0, 0.0));
+		let polys2 = fold_origami(&polys1, &fold2.0, &fold2.1, &p(0.0, 0.0), &Budget::unlimited());
 		printpolys(&polys2);
 		// output looks correct
 	}
+
+	#[test]
+	fn test_fold_over() {
+		let base = vec![Polygon::new(vec![p(0.0, 0.0), p(1.0, 0.0), p(1.0, 1.0), p(0.0, 1.0)])];
+		// fold the right half over onto the left
+		let folded = fold_over(&base, &Line::new(p(0.5, 0.0), p(0.5, 1.0)));
+		assert_eq!(2, folded.len());
+		for poly in folded.iter() {
+			for pt in vec![p(0.0, 0.0), p(0.5, 0.0), p(0.5, 1.0), p(0.0, 1.0)] {
+				assert!(poly.points.contains(&pt));
+			}
+			assert!(!poly.is_hole());
+		}
+		// the flipped half came from the right of the paper
+		assert!(folded[1].source_poly().points.contains(&p(1.0, 1.0)));
+	}
+
+	#[test]
+	fn test_place_square() {
+		let hull = convex_hull(&vec![p(rat(0, 1), rat(0, 1)), p(rat(4, 5), rat(3, 5)), p(rat(1, 5), rat(7, 5))]);
+		let square = place_square(&hull).unwrap();
+		assert_eq!(rat(1, 1), square.signed_area().abs());
+		for pt in hull.points {
+			assert!(square.contains(&pt));
+		}
+	}
+
+	#[test]
+	fn test_wrap_hull() {
+		// right-angled triangle hugging the origin, needs a single diagonal fold
+		let triangle = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 1), rat(0, 1)), p(rat(0, 1), rat(1, 1))]);
+		let state = wrap_hull(&Shape::new(vec![triangle.clone()]), &Budget::unlimited()).unwrap();
+		assert_eq!(2, state.len());
+		for poly in state.iter() {
+			for pt in poly.points.iter() {
+				assert!(triangle.contains(pt));
+			}
+		}
+
+		// problem 2 is the unfolded paper moved over a bit
+		let f = ::std::fs::File::open(format!("{}/00002.problem.txt", ::BASEPATH)).unwrap();
+		let (shape, _) = ::parse::parse::<BigRational, ::std::fs::File>(f).unwrap();
+		let state = wrap_hull(&shape, &Budget::unlimited()).unwrap();
+		assert_eq!(1, state.len());
+		assert_eq!(shape.polys[0].points, state[0].points);
+
+		// out of budget the square is left unfolded, but still there
+		let spent = Budget::unlimited().limit_steps(0);
+		let state = wrap_hull(&Shape::new(vec![triangle.clone()]), &spent).unwrap();
+		assert_eq!(1, state.len());
+		let base = vec![Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 1), rat(0, 1)), p(rat(1, 1), rat(1, 1)), p(rat(0, 1), rat(1, 1))])];
+		let folded = fold_origami(&base, &p(rat(0, 1), rat(0, 1)), &p(rat(1, 1), rat(1, 1)), &p(rat(1, 1), rat(0, 1)), &spent);
+		assert_eq!(base[0].points, folded[0].points);
+
+		// a square twice the size of the paper can't be wrapped, but the
+		// bounding box fold still covers a quarter of it
+		let big = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(2, 1), rat(0, 1)), p(rat(2, 1), rat(2, 1)), p(rat(0, 1), rat(2, 1))]);
+		let problem = Problem{shape: Shape::new(vec![big]), skeleton: Skeleton::new(Vec::new())};
+		assert!(wrap_hull(&problem.shape, &Budget::unlimited()).is_none());
+		let solution = HullStrategy.attempt(&problem, &Budget::unlimited()).unwrap();
+		assert_eq!("bbox", solution.strategy);
+		assert!((solution.resemblance - 0.25).abs() < 0.01);
+	}
+
+	fn problem(id: i64) -> (Shape<BigRational>, Skeleton<BigRational>) {
+		let f = ::std::fs::File::open(format!("{}/{:05}.problem.txt", ::BASEPATH, id)).unwrap();
+		::parse::parse::<BigRational, ::std::fs::File>(f).unwrap()
+	}
+
+	#[test]
+	fn test_solve() {
+		let (shape, skel) = problem(1);
+		let solution = solve(&shape, &skel, &SolveOptions::default()).unwrap();
+		assert_eq!("unfold", solution.strategy);
+		assert_eq!(1.0, solution.resemblance);
+
+		let (shape, skel) = problem(2);
+		let options = SolveOptions{strategies: vec!["hull".to_string()], ..SolveOptions::default()};
+		let solution = solve(&shape, &skel, &options).unwrap();
+		assert_eq!("hull", solution.strategy);
+		assert_eq!(1.0, solution.resemblance);
+	}
+
+	struct Nothing;
+
+	impl Strategy for Nothing {
+		fn name(&self) -> &'static str { "nothing" }
+		fn attempt(&self, _: &Problem, _: &Budget) -> Option<Solution> { None }
+	}
+
+	#[test]
+	fn test_portfolio() {
+		let (shape, skeleton) = problem(2);
+		let two = Problem{shape: shape, skeleton: skeleton};
+		let portfolio = Portfolio::new().add(Nothing).add(HullStrategy).add(GreedyStrategy{max_folds: 2});
+		assert_eq!(vec!["nothing", "hull", "greedy"], portfolio.names());
+		assert_eq!("hull", portfolio.run(&two, &Budget::unlimited()).unwrap().strategy);
+		// out of time, but the hull's placement is already spot on
+		assert_eq!(1.0, portfolio.run(&two, &Budget::unlimited().limit_steps(0)).unwrap().resemblance);
+
+		let portfolio = portfolio.only(&vec!["nothing".to_string()]);
+		assert_eq!(vec!["nothing"], portfolio.names());
+		assert!(portfolio.run(&two, &Budget::unlimited()).is_none());
+
+		let portfolio = Portfolio::new().add(Nothing).add(HullStrategy);
+		assert_eq!(1.0, portfolio.run_parallel(Arc::new(two), &Budget::unlimited()).unwrap().resemblance);
+	}
+
+	#[test]
+	fn test_greedy_holes_only() {
+		// nothing but a hole, so no piece of silhouette to aim for
+		let hole = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(0, 1), rat(1, 1)), p(rat(1, 1), rat(1, 1)), p(rat(1, 1), rat(0, 1))]);
+		let problem = Problem{shape: Shape::new(vec![hole]), skeleton: Skeleton::new(Vec::new())};
+		assert!(GreedyStrategy{max_folds: 2}.attempt(&problem, &Budget::unlimited()).is_none());
+	}
+
+	#[test]
+	fn test_solution_checked() {
+		// half the paper isn't a solution
+		let half = Polygon::new(vec![p(rat(0, 1), rat(0, 1)), p(rat(1, 2), rat(0, 1)), p(rat(1, 2), rat(1, 1)), p(rat(0, 1), rat(1, 1))]);
+		let shape = Shape::new(vec![half.clone()]);
+		match Solution::new("half", &shape, vec![half], BigInt::from(1)) {
+			Err(SolveError::Invalid(_)) => (),
+			other => panic!("expected an invalid solution, got {:?}", other)
+		}
+	}
 }
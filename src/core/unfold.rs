@@ -0,0 +1,321 @@
+// Skeleton driven unfolding: rebuild the unit square from the facets of the
+// folded state by reflecting them back across the creases they share.
+use super::*;
+use super::super::matrix::Matrix33;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+fn same<N: Num>(a: &Point<N>, b: &Point<N>) -> bool {
+	a.cmp(b) == Ordering::Equal
+}
+
+// One layer of paper: a skeleton facet placed back at its source position
+struct Layer<N: Num> {
+	facet: usize,
+	to_src: Matrix33<N>,
+	to_dst: Matrix33<N>,
+	// source edges, counter-clockwise. edges[k] is the image of facet edge k
+	edges: Vec<Line<N>>,
+	triangles: Vec<Polygon<N>>,
+	// true once the paper on the far side of an edge is accounted for
+	resolved: Vec<bool>,
+	// edges of earlier layers that this one resolved, for backtracking
+	matched: Vec<(usize, usize)>,
+}
+
+struct Unfolder<'a, N: Num + 'a> {
+	facets: &'a Vec<Polygon<N>>,
+	edges: Vec<Vec<Line<N>>>,
+	triangles: Vec<Vec<Polygon<N>>>,
+	areas: Vec<N>,
+	// facet on the other side of each facet edge, if any
+	across: Vec<Vec<Option<usize>>>,
+	layers: Vec<Layer<N>>,
+	boundary: Vec<Line<N>>,
+	area: N,
+	steps: usize,
+	max_steps: usize,
+	budget: &'a Budget,
+}
+
+impl<'a, N: Num> Unfolder<'a, N> {
+	fn new(facets: &'a Vec<Polygon<N>>, max_steps: usize, budget: &'a Budget) -> Unfolder<'a, N> {
+		let edges: Vec<Vec<Line<N>>> = facets.iter().map(|f| f.edges()).collect();
+		let mut owner = BTreeMap::new();
+		for (f, es) in edges.iter().enumerate() {
+			for e in es.iter() {
+				owner.insert((e.p1.clone(), e.p2.clone()), f);
+			}
+		}
+		let across = edges.iter().map(|es| {
+			es.iter().map(|e| owner.get(&(e.p2.clone(), e.p1.clone())).cloned()).collect()
+		}).collect();
+		Unfolder{
+			facets: facets,
+			edges: edges,
+			triangles: facets.iter().map(|f| f.triangulate()).collect(),
+			areas: facets.iter().map(|f| f.signed_area()).collect(),
+			across: across,
+			layers: Vec::new(),
+			boundary: Vec::new(),
+			area: N::zero(),
+			steps: 0,
+			max_steps: max_steps,
+			budget: budget,
+		}
+	}
+
+	// Lays facet down in source space, provided it fits with the paper so far
+	fn place(&mut self, facet: usize, to_src: Matrix33<N>) -> bool {
+		let area = self.area.clone() + self.areas[facet].clone();
+		if area > N::one() {
+			return false;
+		}
+		let flipped = to_src.det() < N::zero();
+		let src: Vec<Point<N>> = self.facets[facet].points.iter().map(|p| to_src.transform(p.clone())).collect();
+		for line in self.boundary.iter() {
+			if src.iter().any(|p| side(line, p) < N::zero()) {
+				return false;
+			}
+		}
+		// nothing on the unit square is more than sqrt(2) apart
+		let two = N::one() + N::one();
+		for p in src.iter() {
+			for q in src.iter().chain(self.layers.iter().flat_map(|l| l.edges.iter().map(|e| &e.p1))) {
+				let d = p - q;
+				if d.dot(d.clone()) > two {
+					return false;
+				}
+			}
+		}
+		let triangles: Vec<Polygon<N>> = self.triangles[facet].iter().map(|t| {
+			let mut pts: Vec<Point<N>> = t.points.iter().map(|p| to_src.transform(p.clone())).collect();
+			if flipped {
+				pts.reverse();
+			}
+			Polygon::new(pts)
+		}).collect();
+		for layer in self.layers.iter() {
+			for a in triangles.iter() {
+				if layer.triangles.iter().any(|b| convex_overlap(a, b)) {
+					return false;
+				}
+			}
+		}
+
+		let to_dst = to_src.inverse();
+		let edges: Vec<Line<N>> = self.edges[facet].iter().map(|e| {
+			let (a, b) = (to_src.transform(e.p1.clone()), to_src.transform(e.p2.clone()));
+			if flipped { Line::new(b, a) } else { Line::new(a, b) }
+		}).collect();
+		let mut resolved = vec![false; edges.len()];
+		let mut matched = Vec::new();
+		for (k, e) in edges.iter().enumerate() {
+			for (j, layer) in self.layers.iter().enumerate() {
+				for (m, other) in layer.edges.iter().enumerate() {
+					if same(&e.p1, &other.p2) && same(&e.p2, &other.p1) {
+						// the shared crease has to land in the same place from both sides
+						for p in [&e.p1, &e.p2].iter() {
+							if !same(&to_dst.transform((*p).clone()), &layer.to_dst.transform((*p).clone())) {
+								return false;
+							}
+						}
+						resolved[k] = true;
+						matched.push((j, m));
+					}
+				}
+			}
+		}
+		for &(j, m) in matched.iter() {
+			self.layers[j].resolved[m] = true;
+		}
+		self.layers.push(Layer{
+			facet: facet, to_src: to_src, to_dst: to_dst, edges: edges,
+			triangles: triangles, resolved: resolved, matched: matched
+		});
+		self.area = area;
+		true
+	}
+
+	fn unplace(&mut self) {
+		let layer = self.layers.pop().unwrap();
+		for (j, m) in layer.matched {
+			self.layers[j].resolved[m] = false;
+		}
+		self.area = self.area.clone() - self.areas[layer.facet].clone();
+	}
+
+	// Declares a source edge to be on the edge of the paper. Returns whether a
+	// new boundary line was added, or None if that can't be the case.
+	fn mark_boundary(&mut self, i: usize, k: usize) -> Option<bool> {
+		let line = self.layers[i].edges[k].clone();
+		let d = &line.p2 - &line.p1;
+		if d.dot(d.clone()) > N::one() {
+			return None;
+		}
+		// the paper is convex, so all of it lies to the left of its edges
+		for layer in self.layers.iter() {
+			if layer.edges.iter().any(|e| side(&line, &e.p1) < N::zero()) {
+				return None;
+			}
+		}
+		let known = self.boundary.iter().any(|b| side(b, &line.p1) == N::zero() && side(b, &line.p2) == N::zero());
+		if !known {
+			if self.boundary.len() == 4 {
+				return None;
+			}
+			for b in self.boundary.iter() {
+				let bd = &b.p2 - &b.p1;
+				if cross_scalar(&bd, &d) == N::zero() {
+					// opposite sides of the square are exactly 1 apart
+					let s = side(b, &line.p1);
+					if s.clone() * s != bd.dot(bd.clone()) {
+						return None;
+					}
+				} else if bd.dot(d.clone()) != N::zero() {
+					return None;
+				}
+			}
+			self.boundary.push(line);
+		}
+		self.layers[i].resolved[k] = true;
+		Some(!known)
+	}
+
+	fn search(&mut self) -> bool {
+		self.steps += 1;
+		if self.steps > self.max_steps || !self.budget.step() {
+			return false;
+		}
+		let open = self.layers.iter().enumerate()
+			.filter_map(|(i, l)| l.resolved.iter().position(|r| !r).map(|k| (i, k)))
+			.next();
+		let (i, k) = match open {
+			Some(edge) => edge,
+			None => return self.area == N::one() && self.square().is_some()
+		};
+		let facet = self.layers[i].facet;
+		let to_src = self.layers[i].to_src.clone();
+
+		// the paper carries on flat into the neighbouring facet
+		if let Some(g) = self.across[facet][k] {
+			if self.place(g, to_src.clone()) {
+				if self.search() { return true; }
+				self.unplace();
+			}
+		}
+		// the paper folds back over this facet along the crease
+		let crease = self.edges[facet][k].clone();
+		if self.place(facet, reflect_matrix(&crease.p1, &crease.p2) * to_src) {
+			if self.search() { return true; }
+			self.unplace();
+		}
+		// or it just ends here
+		if let Some(added) = self.mark_boundary(i, k) {
+			if self.search() { return true; }
+			self.layers[i].resolved[k] = false;
+			if added {
+				self.boundary.pop();
+			}
+		}
+		false
+	}
+
+	// If the layers form a unit square, returns the transform taking it back
+	// to the axis aligned square at the origin
+	fn square(&self) -> Option<Matrix33<N>> {
+		let points = self.layers.iter().flat_map(|l| l.edges.iter().map(|e| e.p1.clone())).collect();
+		let hull = convex_hull(&points);
+		if hull.points.len() != 4 {
+			return None;
+		}
+		let edges = hull.edges();
+		for (i, e) in edges.iter().enumerate() {
+			let (d, next) = (&e.p2 - &e.p1, &edges[(i+1)%4].p2 - &edges[(i+1)%4].p1);
+			if d.dot(d.clone()) != N::one() || d.dot(next) != N::zero() {
+				return None;
+			}
+		}
+		let (corner, u) = (hull.points[0].clone(), &hull.points[1] - &hull.points[0]);
+		Some(Matrix33::translate(-corner.x, -corner.y).then_rotate(-u.y, u.x))
+	}
+
+	fn solution(&self) -> Option<Vec<Polygon<N>>> {
+		self.square().map(|normalise| {
+			self.layers.iter().map(|l| {
+				let to_unit = l.to_src.clone() * normalise.clone();
+				Polygon::with_transform(self.facets[l.facet].points.clone(), to_unit.inverse())
+			}).collect()
+		})
+	}
+}
+
+// Facets of the folded state: faces of the skeleton that lie within the silhouette
+pub fn skeleton_facets<N: Num>(shape: &Shape<N>, skel: &Skeleton<N>) -> Vec<Polygon<N>> {
+	faces(&skel.lines).into_iter().filter(|f| {
+		match f.interior_point() {
+			Some(p) => shape.contains(&p),
+			None => false
+		}
+	}).collect()
+}
+
+// Unfolds the skeleton back into the unit square. Starting from a root facet,
+// the paper on the far side of each crease either carries on flat into the
+// neighbouring facet, folds back over the current one, or ends. Returns the
+// destination facets with transforms back to the unit square, or None if no
+// exact unfolding was found within max_steps or the budget.
+pub fn unfold_skeleton<N: Num>(shape: &Shape<N>, skel: &Skeleton<N>, max_steps: usize, budget: &Budget) -> Option<Vec<Polygon<N>>> {
+	let facets = skeleton_facets(shape, skel);
+	// every facet appears somewhere in the paper, so start from the biggest
+	let root = match (0..facets.len()).max_by(|&a, &b| facets[a].signed_area().partial_cmp(&facets[b].signed_area()).unwrap_or(Ordering::Equal)) {
+		Some(root) => root,
+		None => return None
+	};
+	let mut unfolder = Unfolder::new(&facets, max_steps, budget);
+	if unfolder.place(root, Matrix33::identity()) && unfolder.search() {
+		unfolder.solution()
+	} else {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+	use std::fs::File;
+	use ::parse::parse;
+
+	fn problem(id: i64) -> (Shape<BigRational>, Skeleton<BigRational>) {
+		let f = File::open(format!("{}/{:05}.problem.txt", ::BASEPATH, id)).unwrap();
+		parse::<BigRational, File>(f).unwrap()
+	}
+
+	fn check(id: i64) -> Vec<Polygon<BigRational>> {
+		let (shape, skel) = problem(id);
+		let polys = unfold_skeleton(&shape, &skel, 10000, &Budget::unlimited()).unwrap();
+		let mut area = rat(0, 1);
+		for poly in polys.iter() {
+			let src = poly.source_poly();
+			for p in src.points.iter() {
+				assert!(p.x >= rat(0, 1) && p.x <= rat(1, 1) && p.y >= rat(0, 1) && p.y <= rat(1, 1));
+			}
+			area = area + src.signed_area().abs();
+		}
+		assert_eq!(rat(1, 1), area);
+		polys
+	}
+
+	#[test]
+	fn test_unfold_unit_square() {
+		assert_eq!(1, check(1).len());
+	}
+
+	#[test]
+	fn test_unfold_folded_square() {
+		// 5/7 square folded over twice, its four facets unfold to nine layers
+		assert_eq!(9, check(10).len());
+	}
+}
@@ -0,0 +1,178 @@
+// Local checks of a solution, along the lines of the contest's own validator
+use super::*;
+
+use std::cmp::Ordering;
+
+use num::rational::BigRational;
+use ::parse::parse_solution;
+
+// solutions bigger than this (counting non-whitespace characters) are rejected
+pub const MAX_SOLUTION_SIZE: usize = 5000;
+
+// Size as the judges count it, ignoring whitespace. Specs are plain ASCII, so
+// text and raw bytes count the same.
+pub fn spec_size<S: AsRef<[u8]> + ?Sized>(spec: &S) -> usize {
+	spec.as_ref().iter().filter(|&&c| !(c as char).is_whitespace()).count()
+}
+
+// A solution as submitted. `facets` index into both `src` and `dst`.
+#[derive(Debug,Clone)]
+pub struct SolutionSpec<N: Num> {
+	pub src: Vec<Point<N>>,
+	pub facets: Vec<Vec<usize>>,
+	pub dst: Vec<Point<N>>,
+}
+
+fn dist2<N: Num>(a: &Point<N>, b: &Point<N>) -> N {
+	let d = a - b;
+	d.dot(d.clone())
+}
+
+fn bbox_f64<N: Num>(poly: &Polygon<N>) -> (Point<f64>, Point<f64>) {
+	let pts: Vec<Point<f64>> = poly.points.iter().map(|p| p.to_f64()).collect();
+	let mut min = pts[0].clone();
+	let mut max = pts[0].clone();
+	for p in pts.iter() {
+		min.x = min.x.min(p.x);
+		min.y = min.y.min(p.y);
+		max.x = max.x.max(p.x);
+		max.y = max.y.max(p.y);
+	}
+	(min, max)
+}
+
+impl<N: Num> SolutionSpec<N> {
+	pub fn src_polys(&self) -> Vec<Polygon<N>> {
+		self.facets.iter().map(|f| Polygon::new(f.iter().map(|&i| self.src[i].clone()).collect())).collect()
+	}
+
+	pub fn dst_polys(&self) -> Vec<Polygon<N>> {
+		self.facets.iter().map(|f| Polygon::new(f.iter().map(|&i| self.dst[i].clone()).collect())).collect()
+	}
+
+	// Checks everything the judges would, except the size limit which depends
+	// on how the spec is written out
+	pub fn validate(&self) -> Result<(), String> {
+		if self.src.len() != self.dst.len() {
+			return Err(format!("{} source positions but {} destination positions", self.src.len(), self.dst.len()));
+		}
+		let mut sorted = self.src.clone();
+		sorted.sort();
+		if sorted.windows(2).any(|w| w[0].cmp(&w[1]) == Ordering::Equal) {
+			return Err("No coordinate should appear more than once in the source positions part.".to_string());
+		}
+		for (i, p) in self.src.iter().enumerate() {
+			if p.x < N::zero() || p.x > N::one() || p.y < N::zero() || p.y > N::one() {
+				return Err(format!("Source position #{} ({}) is outside the unit square.", i, p));
+			}
+		}
+		for (i, facet) in self.facets.iter().enumerate() {
+			if facet.len() < 3 {
+				return Err(format!("Facet #{} has fewer than three vertices.", i));
+			}
+			if facet.iter().any(|&v| v >= self.src.len()) {
+				return Err(format!("Facet #{} refers to a vertex that doesn't exist.", i));
+			}
+			let mut vertices = facet.clone();
+			vertices.sort();
+			vertices.dedup();
+			if vertices.len() != facet.len() {
+				return Err(format!("Facet #{} has duplicated vertices.", i));
+			}
+			// same distances between every pair of vertices means the facet is
+			// moved by an isometry, maybe flipped
+			for (j, &a) in facet.iter().enumerate() {
+				for &b in facet[j+1..].iter() {
+					if dist2(&self.src[a], &self.src[b]) != dist2(&self.dst[a], &self.dst[b]) {
+						return Err(format!("Facet #{} is not mapped congruently.", i));
+					}
+				}
+			}
+		}
+
+		// facets can't overlap at source positions, so they cover the square
+		// exactly when their areas add up to one
+		let polys: Vec<Polygon<N>> = self.src_polys().into_iter().map(|mut poly| {
+			if poly.signed_area() < N::zero() {
+				poly.points.reverse();
+			}
+			poly
+		}).collect();
+		let mut area = N::zero();
+		for (i, poly) in polys.iter().enumerate() {
+			let a = poly.signed_area();
+			if a == N::zero() {
+				return Err(format!("Facet #{} has no area.", i));
+			}
+			area = area + a;
+		}
+		if area != N::one() {
+			return Err(format!("The union set of all facets at source positions must cover the unit square. Current coverage area = {}", area));
+		}
+		let boxes: Vec<(Point<f64>, Point<f64>)> = polys.iter().map(bbox_f64).collect();
+		let triangles: Vec<Vec<Polygon<N>>> = polys.iter().map(|poly| poly.triangulate()).collect();
+		for i in 0..polys.len() {
+			for j in i+1..polys.len() {
+				let (a, b) = (&boxes[i], &boxes[j]);
+				if a.1.x <= b.0.x || b.1.x <= a.0.x || a.1.y <= b.0.y || b.1.y <= a.0.y {
+					continue;
+				}
+				if triangles[i].iter().any(|s| triangles[j].iter().any(|t| convex_overlap(s, t))) {
+					return Err(format!("Facets #{} and #{} overlap at source positions.", i, j));
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+// Reads a solution exactly as it would be submitted and checks it, size limit
+// and all
+pub fn judge(spec: &str) -> Result<SolutionSpec<BigRational>, String> {
+	let parsed = try!(parse_solution::<BigRational, &[u8]>(spec.as_bytes()).map_err(|e| format!("Invalid solution spec: {:?}", e)));
+	try!(parsed.validate().map_err(|e| format!("Invalid solution spec: {}", e)));
+	let size = spec_size(spec);
+	if size > MAX_SOLUTION_SIZE {
+		return Err(format!("Solution size {} is over the limit of {}", size, MAX_SOLUTION_SIZE));
+	}
+	Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn spec(src: Vec<Point<BigRational>>, facets: Vec<Vec<usize>>, dst: Vec<Point<BigRational>>) -> SolutionSpec<BigRational> {
+		SolutionSpec{src: src, facets: facets, dst: dst}
+	}
+
+	#[test]
+	fn test_validate() {
+		let (zero, half, one) = (rat(0, 1), rat(1, 2), rat(1, 1));
+		let unit = vec![p(zero.clone(), zero.clone()), p(one.clone(), zero.clone()), p(one.clone(), one.clone()), p(zero.clone(), one.clone())];
+		assert!(spec(unit.clone(), vec![vec![0, 1, 2, 3]], unit.clone()).validate().is_ok());
+
+		// folded in half down the middle
+		let src = vec![
+			p(zero.clone(), zero.clone()), p(half.clone(), zero.clone()), p(one.clone(), zero.clone()),
+			p(zero.clone(), one.clone()), p(half.clone(), one.clone()), p(one.clone(), one.clone()),
+		];
+		let mut dst = src.clone();
+		dst[2] = p(zero.clone(), zero.clone());
+		dst[5] = p(zero.clone(), one.clone());
+		let folded = spec(src, vec![vec![0, 1, 4, 3], vec![1, 2, 5, 4]], dst);
+		assert!(folded.validate().is_ok());
+
+		// stretched
+		let mut stretched = folded.clone();
+		stretched.dst[2] = p(rat(-1, 1), zero.clone());
+		assert!(stretched.validate().is_err());
+
+		// only half the paper
+		assert!(spec(folded.src.clone(), vec![vec![0, 1, 4, 3]], folded.dst.clone()).validate().is_err());
+
+		// the same half twice covers the right area but overlaps
+		assert!(spec(folded.src.clone(), vec![vec![0, 1, 4, 3], vec![1, 4, 3, 0]], folded.dst.clone()).validate().is_err());
+	}
+}
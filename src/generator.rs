@@ -0,0 +1,210 @@
+// Making problems of our own: fold the unit square along a sequence of lines,
+// given or picked at random, and write out the solution along with the problem
+// it sets. Everything is exact, so the problem is solved perfectly by its
+// solution.
+use std::collections::BTreeMap;
+
+use num::{BigInt,ToPrimitive,Zero};
+use num::rational::BigRational;
+
+use core;
+use core::{Line,Point,Polygon};
+use publish;
+use publish::MAX_PROBLEM_SIZE;
+
+// xorshift64*, which is plenty for folding paper and gives the same folds for
+// the same seed on any machine
+pub struct XorShift(u64);
+
+impl XorShift {
+	pub fn new(seed: u64) -> XorShift {
+		// all zeroes would stay that way
+		XorShift(if seed == 0 { 0x9e3779b97f4a7c15 } else { seed })
+	}
+
+	pub fn next(&mut self) -> u64 {
+		let mut x = self.0;
+		x ^= x >> 12;
+		x ^= x << 25;
+		x ^= x >> 27;
+		self.0 = x;
+		x.wrapping_mul(0x2545f4914f6cdd1d)
+	}
+
+	// a number from 0 to n-1
+	pub fn below(&mut self, n: u64) -> u64 {
+		self.next() % n
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct RandomFolds {
+	pub seed: u64,
+	// how many folds to make, if the size limits allow
+	pub folds: usize,
+	// fold lines go through points on a grid of this many steps across the
+	// paper, which keeps the fractions small
+	pub denom: i64,
+}
+
+impl Default for RandomFolds {
+	fn default() -> RandomFolds {
+		RandomFolds{seed: 1, folds: 5, denom: 8}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Generated {
+	pub folds: Vec<Line<BigRational>>,
+	pub facets: usize,
+	pub solution: String,
+	pub problem: String,
+}
+
+// The unit square folded along each line in turn, the paper to the right of
+// the line going over onto the left
+pub fn fold(folds: &[Line<BigRational>]) -> Vec<Polygon<BigRational>> {
+	folds.iter().fold(vec![core::unit_square()], |state, line| core::fold_over(&state, line))
+}
+
+// Whether folding along the line would move any paper
+fn cuts(state: &Vec<Polygon<BigRational>>, line: &Line<BigRational>) -> bool {
+	state.iter().any(|poly| poly.points.iter().any(|p| core::side(line, p) < BigRational::zero()))
+}
+
+// The solution spec for a folded state. Vertices are told apart by where they
+// came from, since several layers can end up at the same place.
+pub fn solution_spec(state: &Vec<Polygon<BigRational>>) -> String {
+	let mut index = BTreeMap::new();
+	let (mut src, mut dst) = (Vec::new(), Vec::new());
+	let mut facets = Vec::new();
+	for poly in state {
+		let inverse = poly.transform.inverse();
+		let mut facet = Vec::new();
+		for p in poly.points.iter() {
+			let s = inverse.transform(p.clone());
+			let next = src.len();
+			let i = *index.entry(s.clone()).or_insert(next);
+			if i == next {
+				src.push(s);
+				dst.push(p.clone());
+			}
+			facet.push(i.to_string());
+		}
+		facets.push(format!("{} {}", facet.len(), facet.join(" ")));
+	}
+	let mut out = format!("{}\n", src.len());
+	for p in src {
+		out.push_str(&format!("{}\n", p));
+	}
+	out.push_str(&format!("{}\n", facets.len()));
+	for facet in facets {
+		out.push_str(&format!("{}\n", facet));
+	}
+	for p in dst {
+		out.push_str(&format!("{}\n", p));
+	}
+	out
+}
+
+// The solution and problem for a fold sequence, provided both are within the
+// contest's limits
+pub fn generate(folds: &[Line<BigRational>]) -> Result<Generated, String> {
+	let state = fold(folds);
+	let solution = solution_spec(&state);
+	let problem = publish::problem_spec(&try!(core::judge(&solution)));
+	let problem_size = core::spec_size(&problem);
+	if problem_size > MAX_PROBLEM_SIZE {
+		return Err(format!("Problem size {} is over the limit of {}", problem_size, MAX_PROBLEM_SIZE));
+	}
+	Ok(Generated{folds: folds.to_vec(), facets: state.len(), solution: solution, problem: problem})
+}
+
+// Fold lines run across, up and down, or diagonally. Reflecting in those maps
+// the grid of fold points onto itself, more or less, so the fractions in the
+// specs stay small however many folds there are.
+const DIRECTIONS: [(i64, i64); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+// A point from lo to hi on the grid with steps of 1/denom
+fn on_grid(rng: &mut XorShift, lo: &BigRational, hi: &BigRational, denom: i64) -> BigRational {
+	let d = BigRational::from_integer(BigInt::from(denom));
+	let (first, last) = ((lo.clone() * d.clone()).ceil().to_integer(), (hi.clone() * d).floor().to_integer());
+	let steps = (last - first.clone()).to_u64().unwrap_or(0);
+	BigRational::new(first + BigInt::from(rng.below(steps + 1)), BigInt::from(denom))
+}
+
+// A line through a grid point within the paper's bounding box, in one of the
+// directions above and folding either way
+fn random_line(rng: &mut XorShift, state: &Vec<Polygon<BigRational>>, denom: i64) -> Line<BigRational> {
+	let points: Vec<&Point<BigRational>> = state.iter().flat_map(|poly| poly.points.iter()).collect();
+	let (mut min, mut max) = (points[0].clone(), points[0].clone());
+	for p in points {
+		if p.x < min.x { min.x = p.x.clone(); }
+		if p.y < min.y { min.y = p.y.clone(); }
+		if p.x > max.x { max.x = p.x.clone(); }
+		if p.y > max.y { max.y = p.y.clone(); }
+	}
+	let p1 = Point{x: on_grid(rng, &min.x, &max.x, denom), y: on_grid(rng, &min.y, &max.y, denom)};
+	let (dx, dy) = DIRECTIONS[rng.below(DIRECTIONS.len() as u64) as usize];
+	let sign = if rng.below(2) == 0 { 1 } else { -1 };
+	let d = Point{x: BigRational::from_integer(BigInt::from(sign * dx)), y: BigRational::from_integer(BigInt::from(sign * dy))};
+	let p2 = &p1 + d;
+	Line::new(p1, p2)
+}
+
+// Folds at random, skipping lines that miss the paper or would take either
+// spec over the size limit, until enough folds are made or it's clear no more
+// will fit
+pub fn random(options: &RandomFolds) -> Generated {
+	let mut rng = XorShift::new(options.seed);
+	let mut best = generate(&[]).unwrap();
+	let mut folds = Vec::new();
+	let mut attempts = 0;
+	while folds.len() < options.folds && attempts < options.folds * 50 {
+		attempts += 1;
+		let state = fold(&folds);
+		let line = random_line(&mut rng, &state, options.denom.max(1));
+		if !cuts(&state, &line) {
+			continue;
+		}
+		folds.push(line);
+		match generate(&folds) {
+			Ok(generated) => best = generated,
+			Err(_) => { folds.pop(); }
+		}
+	}
+	best
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core;
+	use parse;
+	use num::rational::BigRational;
+
+	#[test]
+	fn test_generate() {
+		// in half and in half again
+		let folds: Vec<Line<BigRational>> = vec!["1/2,0 1/2,1".parse().unwrap(), "1,1/2 0,1/2".parse().unwrap()];
+		let generated = generate(&folds).unwrap();
+		assert_eq!(4, generated.facets);
+		assert_eq!("1\n4\n0,0\n1/2,0\n1/2,1/2\n0,1/2\n4\n1/2,1/2 0,1/2\n1/2,1/2 1/2,0\n0,1/2 0,0\n0,0 1/2,0\n", generated.problem);
+
+		let (shape, _) = parse::parse::<BigRational, &[u8]>(generated.problem.as_bytes()).unwrap();
+		let solution = core::judge(&generated.solution).unwrap();
+		assert_eq!(1.0, core::resemblance(&shape, &solution.dst_polys()));
+	}
+
+	#[test]
+	fn test_random() {
+		let options = RandomFolds{seed: 42, folds: 4, denom: 4};
+		let generated = random(&options);
+		assert_eq!(generated.solution, random(&options).solution);
+		assert!(generated.folds.len() <= 4 && generated.facets > generated.folds.len());
+		let (shape, skeleton) = parse::parse::<BigRational, &[u8]>(generated.problem.as_bytes()).unwrap();
+		assert!(!skeleton.lines.is_empty());
+		let solution = core::judge(&generated.solution).unwrap();
+		assert_eq!(1.0, core::resemblance(&shape, &solution.dst_polys()));
+	}
+}
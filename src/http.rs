@@ -0,0 +1,437 @@
+// Just enough HTTP/1.1 for the contest API: GETs and multipart form POSTs over
+// plain http, with gzip, chunked replies, redirects and the API key header.
+use std::fmt;
+use std::io;
+use std::io::{BufRead,BufReader,Read,Write};
+use std::net::TcpStream;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use rustc_serialize::json::Json;
+
+const MAX_REDIRECTS: usize = 5;
+
+#[derive(Debug)]
+pub enum HttpError {
+	Io(io::Error),
+	BadUrl(String),
+	BadResponse(String),
+	TooManyRedirects,
+	// the server answered, but not with success
+	Status(u16, String),
+}
+
+impl From<io::Error> for HttpError {
+	fn from(e: io::Error) -> HttpError {
+		HttpError::Io(e)
+	}
+}
+
+impl HttpError {
+	// Failures worth trying again: the connection dropping, the rate limit,
+	// or the server having a bad moment
+	pub fn transient(&self) -> bool {
+		match *self {
+			HttpError::Io(_) => true,
+			HttpError::Status(code, _) => code == 429 || code >= 500,
+			_ => false
+		}
+	}
+
+	// Failures where the server surely didn't act on the request, so even an
+	// upload can safely be sent again. A dropped connection may have been
+	// dropped after the server got it all.
+	pub fn refused(&self) -> bool {
+		match *self {
+			HttpError::Status(429, _) => true,
+			_ => false
+		}
+	}
+}
+
+impl fmt::Display for HttpError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			HttpError::Io(ref e) => write!(f, "{}", e),
+			HttpError::BadUrl(ref url) => write!(f, "Can't handle url {}", url),
+			HttpError::BadResponse(ref why) => write!(f, "Bad response: {}", why),
+			HttpError::TooManyRedirects => write!(f, "Too many redirects"),
+			HttpError::Status(code, ref body) => write!(f, "HTTP {}: {}", code, body),
+		}
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+struct Url {
+	host: String,
+	port: u16,
+	path: String,
+}
+
+impl Url {
+	fn parse(url: &str) -> Result<Url, HttpError> {
+		if !url.starts_with("http://") {
+			return Err(HttpError::BadUrl(url.to_string()));
+		}
+		let rest = &url["http://".len()..];
+		let (authority, path) = match rest.find('/') {
+			Some(i) => (&rest[..i], &rest[i..]),
+			None => (rest, "/")
+		};
+		let (host, port) = match authority.rfind(':') {
+			Some(i) => match authority[i+1..].parse::<u16>() {
+				Ok(port) => (&authority[..i], port),
+				Err(_) => return Err(HttpError::BadUrl(url.to_string()))
+			},
+			None => (authority, 80)
+		};
+		if host.is_empty() {
+			return Err(HttpError::BadUrl(url.to_string()));
+		}
+		Ok(Url{host: host.to_string(), port: port, path: path.to_string()})
+	}
+
+	// Where a redirect's Location points, relative to this url
+	fn join(&self, location: &str) -> Result<Url, HttpError> {
+		if location.starts_with("http://") {
+			Url::parse(location)
+		} else if location.starts_with('/') {
+			Ok(Url{host: self.host.clone(), port: self.port, path: location.to_string()})
+		} else {
+			Err(HttpError::BadUrl(location.to_string()))
+		}
+	}
+
+	// What goes in the Host header, which leaves out the default port
+	fn authority(&self) -> String {
+		if self.port == 80 {
+			self.host.clone()
+		} else {
+			format!("{}:{}", self.host, self.port)
+		}
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Response {
+	pub status: u16,
+	// names lower cased
+	pub headers: Vec<(String, String)>,
+	// already decompressed
+	pub body: Vec<u8>,
+}
+
+impl Response {
+	pub fn header(&self, name: &str) -> Option<&str> {
+		let name = name.to_lowercase();
+		self.headers.iter().find(|h| h.0 == name).map(|h| h.1.as_str())
+	}
+
+	pub fn text(&self) -> String {
+		String::from_utf8_lossy(&self.body).into_owned()
+	}
+
+	pub fn json(&self) -> Result<Json, HttpError> {
+		Json::from_str(&self.text()).map_err(|e| HttpError::BadResponse(format!("{}", e)))
+	}
+}
+
+// A field of a multipart form
+#[derive(Debug,Clone)]
+pub enum Part {
+	Text(String, String),
+	// field name, file name and contents
+	File(String, String, Vec<u8>),
+}
+
+fn multipart(parts: &[Part], boundary: &str) -> Vec<u8> {
+	let mut body = Vec::new();
+	for part in parts {
+		body.extend(format!("--{}\r\n", boundary).into_bytes());
+		match *part {
+			Part::Text(ref name, ref value) => {
+				body.extend(format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", name, value).into_bytes());
+			},
+			Part::File(ref name, ref filename, ref data) => {
+				body.extend(format!("Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n", name, filename).into_bytes());
+				body.extend(b"Content-Type: application/octet-stream\r\n\r\n".iter().cloned());
+				body.extend(data.iter().cloned());
+				body.extend(b"\r\n".iter().cloned());
+			}
+		}
+	}
+	body.extend(format!("--{}--\r\n", boundary).into_bytes());
+	body
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, HttpError> {
+	let mut body = Vec::new();
+	loop {
+		let mut line = String::new();
+		try!(reader.read_line(&mut line));
+		let size = line.trim().split(';').next().unwrap_or("");
+		let size = try!(usize::from_str_radix(size, 16).map_err(|_| HttpError::BadResponse(format!("bad chunk size {:?}", line))));
+		if size == 0 {
+			break;
+		}
+		let mut chunk = vec![0; size];
+		try!(reader.read_exact(&mut chunk));
+		body.extend(chunk);
+		// the CRLF after each chunk
+		let mut crlf = String::new();
+		try!(reader.read_line(&mut crlf));
+	}
+	Ok(body)
+}
+
+fn read_response<R: Read>(stream: R) -> Result<Response, HttpError> {
+	let mut reader = BufReader::new(stream);
+	let mut line = String::new();
+	try!(reader.read_line(&mut line));
+	let status = match line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()) {
+		Some(status) if line.starts_with("HTTP/1.") => status,
+		_ => return Err(HttpError::BadResponse(format!("status line {:?}", line)))
+	};
+	let mut headers = Vec::new();
+	loop {
+		let mut line = String::new();
+		if try!(reader.read_line(&mut line)) == 0 || line.trim().is_empty() {
+			break;
+		}
+		if let Some(i) = line.find(':') {
+			headers.push((line[..i].trim().to_lowercase(), line[i+1..].trim().to_string()));
+		}
+	}
+	let mut response = Response{status: status, headers: headers, body: Vec::new()};
+
+	let chunked = response.header("transfer-encoding").map_or(false, |te| te.to_lowercase().contains("chunked"));
+	let length = response.header("content-length").and_then(|l| l.parse::<usize>().ok());
+	let mut body = Vec::new();
+	if chunked {
+		body = try!(read_chunked(&mut reader));
+	} else if let Some(length) = length {
+		body.resize(length, 0);
+		try!(reader.read_exact(&mut body));
+	} else {
+		try!(reader.read_to_end(&mut body));
+	}
+	if response.header("content-encoding").map_or(false, |ce| ce.to_lowercase().contains("gzip")) {
+		let mut decoded = Vec::new();
+		try!(try!(GzDecoder::new(&body[..])).read_to_end(&mut decoded));
+		body = decoded;
+	}
+	response.body = body;
+	Ok(response)
+}
+
+pub struct Client {
+	// eg. http://2016sv.icfpcontest.org/api, paths are relative to it
+	pub base_url: String,
+	pub api_key: String,
+	pub timeout: Duration,
+}
+
+impl Client {
+	pub fn new(base_url: &str, api_key: &str) -> Client {
+		Client{base_url: base_url.trim_end_matches('/').to_string(), api_key: api_key.to_string(), timeout: Duration::from_secs(60)}
+	}
+
+	fn url(&self, path: &str) -> Result<Url, HttpError> {
+		Url::parse(&format!("{}/{}", self.base_url, path.trim_start_matches('/')))
+	}
+
+	pub fn get(&self, path: &str) -> Result<Response, HttpError> {
+		let url = try!(self.url(path));
+		self.request("GET", url, None, &[])
+	}
+
+	pub fn post_form(&self, path: &str, parts: &[Part]) -> Result<Response, HttpError> {
+		let url = try!(self.url(path));
+		let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+		let boundary = format!("superlegit{:09}", nanos);
+		let body = multipart(parts, &boundary);
+		self.request("POST", url, Some(format!("multipart/form-data; boundary={}", boundary)), &body)
+	}
+
+	// Follows redirects, and turns anything but a 2xx into an error. The API key
+	// only goes to the host and port we started at, never where a redirect
+	// points elsewhere.
+	fn request(&self, method: &str, url: Url, content_type: Option<String>, body: &[u8]) -> Result<Response, HttpError> {
+		let origin = (url.host.clone(), url.port);
+		let (mut method, mut url, mut content_type, mut body) = (method.to_string(), url, content_type, body);
+		for _ in 0..MAX_REDIRECTS + 1 {
+			let api_key = if (url.host.clone(), url.port) == origin { Some(self.api_key.as_str()) } else { None };
+			let response = try!(self.send(&method, &url, content_type.as_ref(), body, api_key));
+			if response.status >= 200 && response.status < 300 {
+				return Ok(response);
+			}
+			match response.status {
+				301 | 302 | 303 | 307 | 308 => {
+					let location = match response.header("location") {
+						Some(location) => location.to_string(),
+						None => return Err(HttpError::BadResponse("redirect without a location".to_string()))
+					};
+					url = try!(url.join(&location));
+					// only 307 and 308 ask for the same request again
+					if response.status != 307 && response.status != 308 {
+						method = "GET".to_string();
+						content_type = None;
+						body = &[];
+					}
+				},
+				status => return Err(HttpError::Status(status, response.text()))
+			}
+		}
+		Err(HttpError::TooManyRedirects)
+	}
+
+	fn send(&self, method: &str, url: &Url, content_type: Option<&String>, body: &[u8], api_key: Option<&str>) -> Result<Response, HttpError> {
+		let mut stream = try!(TcpStream::connect((url.host.as_str(), url.port)));
+		try!(stream.set_read_timeout(Some(self.timeout)));
+		try!(stream.set_write_timeout(Some(self.timeout)));
+		let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\nAccept-Encoding: gzip\r\nConnection: close\r\n",
+			method, url.path, url.authority());
+		if let Some(api_key) = api_key {
+			head.push_str(&format!("X-API-Key: {}\r\n", api_key));
+		}
+		if let Some(content_type) = content_type {
+			head.push_str(&format!("Content-Type: {}\r\n", content_type));
+		}
+		if method == "POST" || !body.is_empty() {
+			head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+		}
+		head.push_str("\r\n");
+		try!(stream.write_all(head.as_bytes()));
+		try!(stream.write_all(body));
+		try!(stream.flush());
+		read_response(stream)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use flate2::Compression;
+	use flate2::write::GzEncoder;
+	use std::net::TcpListener;
+	use std::thread;
+
+	// Reads one request off the connection, headers and body
+	fn read_request(stream: &mut TcpStream) -> String {
+		let mut reader = BufReader::new(stream);
+		let mut request = String::new();
+		let mut length = 0;
+		loop {
+			let mut line = String::new();
+			reader.read_line(&mut line).unwrap();
+			if line.to_lowercase().starts_with("content-length:") {
+				length = line[15..].trim().parse::<usize>().unwrap();
+			}
+			request.push_str(&line);
+			if line == "\r\n" {
+				break;
+			}
+		}
+		let mut body = vec![0; length];
+		reader.read_exact(&mut body).unwrap();
+		request + &String::from_utf8(body).unwrap()
+	}
+
+	// A stand-in server giving each canned reply to one connection in turn.
+	// Returns its base url, and the requests it got once it's done.
+	fn serve(replies: Vec<Vec<u8>>) -> (String, thread::JoinHandle<Vec<String>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let base = format!("http://127.0.0.1:{}/api", listener.local_addr().unwrap().port());
+		let handle = thread::spawn(move || {
+			replies.into_iter().map(|reply| {
+				let (mut stream, _) = listener.accept().unwrap();
+				let request = read_request(&mut stream);
+				stream.write_all(&reply).unwrap();
+				request
+			}).collect()
+		});
+		(base, handle)
+	}
+
+	#[test]
+	fn test_refused() {
+		let dropped = HttpError::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+		assert!(dropped.transient() && !dropped.refused());
+		assert!(!HttpError::Status(503, String::new()).refused());
+		assert!(HttpError::Status(429, String::new()).refused());
+	}
+
+	#[test]
+	fn test_url() {
+		assert_eq!(Url{host: "example.org".to_string(), port: 80, path: "/".to_string()}, Url::parse("http://example.org").unwrap());
+		let url = Url::parse("http://127.0.0.1:8080/api/blob/abc").unwrap();
+		assert_eq!(("127.0.0.1", 8080, "/api/blob/abc"), (url.host.as_str(), url.port, url.path.as_str()));
+		assert_eq!("/elsewhere", url.join("/elsewhere").unwrap().path);
+		assert_eq!("127.0.0.1:8080", url.authority());
+		assert_eq!("example.org", Url::parse("http://example.org:80/").unwrap().authority());
+		assert!(Url::parse("https://example.org/").is_err());
+	}
+
+	#[test]
+	fn test_get() {
+		let mut gz = GzEncoder::new(Vec::new(), Compression::Default);
+		gz.write_all(b"{\"ok\":true}").unwrap();
+		let gzipped = gz.finish().unwrap();
+		let mut reply = format!("HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", gzipped.len()).into_bytes();
+		reply.extend(gzipped);
+		let replies = vec![
+			b"HTTP/1.1 302 Found\r\nLocation: /api/moved\r\nContent-Length: 0\r\n\r\n".to_vec(),
+			reply,
+			b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n".to_vec(),
+			b"HTTP/1.1 403 Forbidden\r\n\r\nbad key".to_vec(),
+		];
+		let (base, server) = serve(replies);
+		let client = Client::new(&base, "secret");
+		let response = client.get("snapshot/list").unwrap();
+		assert_eq!(Some(true), response.json().unwrap().find("ok").and_then(|ok| ok.as_boolean()));
+		assert_eq!("hello, world", client.get("/blob/abc").unwrap().text());
+		match client.get("blob/abc") {
+			Err(HttpError::Status(403, body)) => assert_eq!("bad key", body),
+			other => panic!("expected a 403, got {:?}", other)
+		}
+
+		let requests = server.join().unwrap();
+		assert!(requests[0].starts_with("GET /api/snapshot/list HTTP/1.1\r\n"));
+		assert!(requests[0].contains("X-API-Key: secret\r\n"));
+		assert!(requests[0].contains(&format!("Host: {}\r\n", &base["http://".len()..base.len() - "/api".len()])));
+		assert!(requests[1].contains("X-API-Key: secret\r\n"));
+		assert!(requests[0].contains("Accept-Encoding: gzip\r\n"));
+		assert!(requests[1].starts_with("GET /api/moved HTTP/1.1\r\n"));
+		assert!(requests[2].starts_with("GET /api/blob/abc HTTP/1.1\r\n"));
+	}
+
+	#[test]
+	fn test_redirect_elsewhere() {
+		// the key stays behind when a redirect leads to another server
+		let (elsewhere, other) = serve(vec![b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok".to_vec()]);
+		let redirect = format!("HTTP/1.1 302 Found\r\nLocation: {}/blob\r\nContent-Length: 0\r\n\r\n", elsewhere);
+		let (base, server) = serve(vec![redirect.into_bytes()]);
+		let client = Client::new(&base, "secret");
+		assert_eq!("ok", client.get("blob/abc").unwrap().text());
+
+		assert!(server.join().unwrap()[0].contains("X-API-Key: secret\r\n"));
+		let request = other.join().unwrap().remove(0);
+		assert!(request.starts_with("GET /api/blob HTTP/1.1\r\n"));
+		assert!(!request.contains("X-API-Key"));
+	}
+
+	#[test]
+	fn test_post_form() {
+		let (base, server) = serve(vec![b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n{\"ok\":true}".to_vec()]);
+		let client = Client::new(&base, "secret");
+		let parts = vec![Part::Text("problem_id".to_string(), "42".to_string()), Part::File("solution_spec".to_string(), "00042.solution.txt".to_string(), b"4\n0,0\n".to_vec())];
+		assert!(client.post_form("solution/submit", &parts).is_ok());
+
+		let request = server.join().unwrap().remove(0);
+		assert!(request.starts_with("POST /api/solution/submit HTTP/1.1\r\n"));
+		let boundary = request.split("boundary=").nth(1).unwrap().split("\r\n").next().unwrap();
+		assert!(request.contains(&format!("--{}\r\nContent-Disposition: form-data; name=\"problem_id\"\r\n\r\n42\r\n", boundary)));
+		assert!(request.contains("name=\"solution_spec\"; filename=\"00042.solution.txt\"\r\nContent-Type: application/octet-stream\r\n\r\n4\n0,0\n\r\n"));
+		assert!(request.ends_with(&format!("--{}--\r\n", boundary)));
+	}
+}
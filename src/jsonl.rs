@@ -0,0 +1,34 @@
+// Files of one JSON record per line, only ever appended to, as the results
+// and publications are kept
+use std::fs;
+use std::io;
+use std::io::{BufRead,BufReader,Write};
+
+use rustc_serialize::json::{Json,ToJson};
+
+// Every record in the file, skipping any lines that don't make sense and
+// saying so. No file just means no records yet.
+pub fn load<T, F: Fn(&Json) -> Option<T>>(path: &str, what: &str, from_json: F) -> io::Result<Vec<T>> {
+	let file = match fs::File::open(path) {
+		Ok(file) => file,
+		Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e)
+	};
+	let mut records = Vec::new();
+	for line in BufReader::new(file).lines() {
+		let line = try!(line);
+		if line.trim().is_empty() {
+			continue;
+		}
+		match Json::from_str(&line).ok().and_then(|json| from_json(&json)) {
+			Some(record) => records.push(record),
+			None => println!("Skipping bad {}: {}", what, line)
+		}
+	}
+	Ok(records)
+}
+
+pub fn append<T: ToJson>(path: &str, record: &T) -> io::Result<()> {
+	let mut file = try!(fs::OpenOptions::new().create(true).append(true).open(path));
+	file.write_all(format!("{}\n", record.to_json()).as_bytes())
+}
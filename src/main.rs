@@ -1,37 +1,182 @@
 #![allow(non_snake_case)]
 
+extern crate flate2;
 extern crate num;
 extern crate rustc_serialize;
+extern crate sha1;
 extern crate svg;
+use std::io::{Read,Write};
 use std::vec::Vec;
 use std::path::Path;
-use rustc_serialize::json::Json;
+use std::time::Duration;
 
+mod batch;
+mod blobcache;
+mod config;
 mod core;
+mod generator;
+mod http;
+mod jsonl;
 mod matrix;
+mod mockserver;
+mod models;
 mod parse;
+mod publish;
+mod ratelimit;
 mod rendersvg;
 mod restapi;
+mod results;
+mod scoring;
+mod submission;
+mod submissionlog;
+mod sync;
 mod write;
 
 use num::rational::BigRational;
 
+// where problems and solutions live unless the config says otherwise
 pub const BASEPATH: &'static str = "icfp2016problems";
 
-fn draw_problems(problems: Vec<Json>, attempts: i64) -> Vec<Json> {
+fn load_problem(data_dir: &str, id: i64) -> Result<(core::Shape<BigRational>, core::Skeleton<BigRational>), parse::ParseError> {
+	let file = try!(std::fs::File::open(format!("{}/{:05}.problem.txt", data_dir, id)));
+	parse::parse::<BigRational, std::fs::File>(file)
+}
+
+// Solves a problem, saving the solution and a drawing of it
+fn solve_problem(config: &config::Config, id: i64, shape: core::Shape<BigRational>, skeleton: core::Skeleton<BigRational>, options: &core::SolveOptions) {
+	let filename = format!("{}/{:05}.problem.svg", config.data_dir, id);
+	match core::solve(&shape, &skeleton, options) {
+		Ok(solution) => {
+			println!("Problem {} solved by {} with resemblance {}", id, solution.strategy, solution.resemblance);
+			batch::save_solution(&config.data_dir, id, &solution.spec).unwrap();
+			let record = results::Record::new(id, &solution.strategy, &solution.spec, Some(solution.resemblance));
+			results::Results::append(&results::results_path(&config.data_dir), &record).unwrap();
+			let spec = parse::parse_solution::<BigRational, &[u8]>(solution.spec.as_bytes()).ok();
+			rendersvg::draw_svg(shape, skeleton, spec.as_ref(), &filename);
+		},
+		Err(e) => {
+			match e {
+				core::SolveError::Unsolved => println!("Problem {} not solved", id),
+				core::SolveError::Invalid(why) => println!("Problem {} has no valid solution: {}", id, why)
+			}
+			rendersvg::draw_svg(shape, skeleton, None, &filename);
+		}
+	}
+}
+
+// Value of a --name=<n> flag
+fn flag(flags: &Vec<String>, name: &str) -> Option<u64> {
+	let prefix = format!("--{}=", name);
+	flags.iter().filter(|f| f.starts_with(&prefix)).filter_map(|f| f[prefix.len()..].parse::<u64>().ok()).next()
+}
+
+// Solver options from the config and then the command line. Plain arguments
+// restrict which strategies are tried, --parallel runs them all at once, and
+// --time=<seconds> or --steps=<n> cut them short.
+fn solve_options(config: &config::Config, args: Vec<String>) -> core::SolveOptions {
+	let (flags, strategies): (Vec<String>, Vec<String>) = args.into_iter().partition(|a| a.starts_with("--"));
+	let mut options = config.solve_options();
+	if !strategies.is_empty() {
+		options.strategies = strategies;
+	}
+	options.parallel = flags.iter().any(|f| f == "--parallel");
+	if let Some(seconds) = flag(&flags, "time") {
+		options.time_limit = Some(Duration::from_secs(seconds));
+	}
+	options.step_limit = flag(&flags, "steps").map(|n| n as usize);
+	options
+}
+
+fn score(resemblance: Option<f64>) -> String {
+	resemblance.map_or("-".to_string(), |r| r.to_string())
+}
+
+// Uploads the solutions that could improve our score, recording what the
+// server makes of each. Returns the problems whose upload failed.
+fn submit_better(config: &config::Config, ids: Vec<i64>, dry_run: bool) -> Vec<i64> {
+	let path = results::results_path(&config.data_dir);
+	let results = results::Results::load(&path).unwrap();
+	let api = restapi::Api::new(config);
+	let mut counts = std::collections::BTreeMap::new();
+	let mut failed = Vec::new();
+	for id in ids {
+		let (decision, record) = submission::consider(&config.data_dir, id, &results);
+		*counts.entry(decision.kind()).or_insert(0) += 1;
+		if decision != submission::Decision::NoSolution {
+			println!("Problem {}: {}", id, decision);
+		}
+		let mut record = match (decision, record) {
+			(submission::Decision::Submit(..), Some(record)) if !dry_run => record,
+			_ => continue
+		};
+		match api.submit(id) {
+			Ok(reply) => {
+				println!("{}", reply);
+				record.server_resemblance = reply.resemblance;
+				record.error = reply.error;
+				results::Results::append(&path, &record).unwrap();
+			},
+			Err(e) => {
+				println!("Submission of {} failed: {}", id, e);
+				failed.push(id);
+			}
+		}
+	}
+	let counts: Vec<String> = counts.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect();
+	println!("{}{}", if dry_run { "Dry run: " } else { "" }, counts.join(", "));
+	failed
+}
+
+// Sets the solution at path as a problem, at the publish time asked for or
+// the next free slot. The problem it sets is saved next to it as
+// NNNNN.ourproblem.txt, and with --dry-run that's as far as it goes.
+fn publish_solution(config: &config::Config, path: &str, at: Option<i64>, dry_run: bool) -> Result<(), String> {
+	let mut spec = String::new();
+	try!(std::fs::File::open(path).and_then(|mut f| f.read_to_string(&mut spec)).map_err(|e| format!("Can't read {}: {}", path, e)));
+	let prepared = try!(publish::prepare(&spec));
+	let published = publish::published_path(&config.data_dir);
+	let publications = publish::Publications::load(&published).unwrap();
+	if let Some(old) = publications.find(&prepared.solution_hash) {
+		return Err(format!("Already published as problem {} at {}", old.problem_id, old.publish_time));
+	}
+	let problem_path = format!("{}.ourproblem.txt", path.trim_end_matches(".solution.txt"));
+	try!(std::fs::File::create(&problem_path).and_then(|mut f| f.write_all(prepared.problem.as_bytes())).map_err(|e| format!("Can't write {}: {}", problem_path, e)));
+	println!("Solution size {}, problem size {} in {}", prepared.solution_size, prepared.problem_size, problem_path);
+	let time = publish::choose_time(at, results::now() as i64, &publications.slots());
+	if dry_run {
+		match time {
+			Ok(time) => println!("Would publish at {}", time),
+			Err(why) => println!("Couldn't publish: {}", why)
+		}
+		return Ok(());
+	}
+	let time = try!(time);
+	let reply = try!(restapi::Api::new(config).submit_problem(path, time).map_err(|e| format!("Submission failed: {}", e)));
+	println!("{}", reply);
+	if !reply.ok {
+		return Err(reply.to_string());
+	}
+	let record = try!(publish::Publication::new(&reply, &prepared, time).ok_or("The server didn't say which problem it is".to_string()));
+	if record.problem_hash != prepared.problem_hash {
+		println!("The server's problem {} has hash {}, ours {}", record.problem_id, record.problem_hash, prepared.problem_hash);
+	}
+	publish::Publications::append(&published, &record).unwrap();
+	Ok(())
+}
+
+fn draw_problems(config: &config::Config, problems: &[models::ProblemMeta], attempts: i64) {
 	println!("Attempting to solve {}/{} problems", attempts, problems.len());
 	let mut skipped = 0;
 	let mut attempted = 0;
-	for problem in &problems {
-		let id = problem.find_path(&["problem_id"]).unwrap().as_i64().unwrap();
-		let filename = format!("{:05}.problem.svg", id);
-		let file = std::fs::File::open(format!("{}/{:05}.problem.txt", BASEPATH, id)).unwrap();
-		let solpath = format!("{}/{:05}.solution.txt", BASEPATH, id);
+	let options = config.solve_options();
+	for problem in problems {
+		let id = problem.problem_id;
+		let solpath = format!("{}/{:05}.solution.txt", config.data_dir, id);
 		let solution = Path::new(&solpath);
-		let data = parse::parse::<BigRational, std::fs::File>(file);
+		let data = load_problem(&config.data_dir, id);
 		if data.is_ok() && !solution.exists() {
 			let (shape, skeleton) = data.unwrap();
-			rendersvg::draw_svg(shape, skeleton, &filename);
+			solve_problem(config, id, shape, skeleton, &options);
 			attempted += 1;
 			if attempted > attempts { break }
 		} else if !solution.exists() {
@@ -41,15 +186,33 @@ fn draw_problems(problems: Vec<Json>, attempts: i64) -> Vec<Json> {
 		}
 	}
 	println!("Skipped {} with existing solutions", skipped);
-	return problems
 }
 
 fn main() {
 	use std::env;
 	use std::process;
+	let config = match config::Config::load() {
+		Ok(config) => config,
+		Err(e) => {
+			println!("{}", e);
+			process::exit(1);
+		}
+	};
 	// setup directories for outputs
-	std::fs::create_dir_all(BASEPATH).unwrap();
-    let help_string = "Cmds: updatecontest, drawproblem, solveproblem, ...";
+	std::fs::create_dir_all(&config.data_dir).unwrap();
+	// the API commands can't do anything without a key
+	let need_key = || if config.api_key.is_empty() {
+		println!("No API key: set api_key in {} or SUPERLEGIT_API_KEY", config::CONFIG_FILE);
+		process::exit(1);
+	};
+	let contest = |api: &restapi::Api| match api.get_contest_meta() {
+		Ok(contest) => contest,
+		Err(e) => {
+			println!("Couldn't get the contest: {}", e);
+			process::exit(1);
+		}
+	};
+    let help_string = "Cmds: updatecontest, drawproblems, drawproblem, solveproblem, hullsolve, unfoldsolve, batch, results, importlog, score, submit, generate, publish, submitproblem, mockserver";
 	if env::args().len() < 2 {
 		println!("{:?}", help_string);
 		process::exit(1);
@@ -59,33 +222,250 @@ fn main() {
 	println!("Running {:?}", cmd);
 	match cmd.trim() {
 		"updatecontest" => {
-			// remove cached files
-			std::fs::remove_file(format!("{}/contest_list.json", BASEPATH)).unwrap();
-			std::fs::remove_file(format!("{}/contest.json", BASEPATH)).unwrap();
-			// grab contest snapshots
-			let problems = restapi::get_contest_meta();
-			// save the problem blobs
-			restapi::save_problems(problems);
+			// fetches the newest snapshot and any problems we don't have, and
+			// says what changed since last time
+			need_key();
+			match sync::sync(&restapi::Api::new(&config), &config.data_dir) {
+				Ok((diff, failed)) => {
+					print!("{}", diff);
+					if !failed.is_empty() {
+						println!("Couldn't download problems {:?}", failed);
+					}
+				},
+				Err(e) => {
+					println!("Couldn't sync with the contest: {}", e);
+					process::exit(1);
+				}
+			}
 		},
 		"drawproblems" => {
 			// draw svgs of each one, attempts is numner to try
-			let problems = restapi::get_contest_meta();
-			let problems = restapi::save_problems(problems);
+			need_key();
+			let api = restapi::Api::new(&config);
+			let contest = contest(&api);
+			api.save_problems(&contest.problems);
 			let attempts = env::args().nth(2).unwrap_or("100".to_string()).parse::<i64>().unwrap_or(100);
-			draw_problems(problems, attempts);
+			draw_problems(&config, &contest.problems, attempts);
 		},
 		"drawproblem" => {
+			// draws the problem along with any solution we already have
 			let id = env::args().nth(2).unwrap().parse::<i64>().unwrap();
-			let filename = format!("{:05}.problem.svg", id);
-			let file = std::fs::File::open(format!("{}/{:05}.problem.txt", BASEPATH, id)).unwrap();
-			let (shape, skeleton) = parse::parse::<BigRational, std::fs::File>(file).unwrap();
-			rendersvg::draw_svg(shape, skeleton, &filename)
+			let (shape, skeleton) = load_problem(&config.data_dir, id).unwrap();
+			let solution = std::fs::File::open(format!("{}/{:05}.solution.txt", config.data_dir, id)).ok()
+				.and_then(|f| parse::parse_solution::<BigRational, std::fs::File>(f).ok());
+			rendersvg::draw_svg(shape, skeleton, solution.as_ref(), &format!("{}/{:05}.problem.svg", config.data_dir, id))
+		},
+		"solveproblem" => {
+			let id = env::args().nth(2).unwrap().parse::<i64>().unwrap();
+			let (shape, skeleton) = load_problem(&config.data_dir, id).unwrap();
+			solve_problem(&config, id, shape, skeleton, &solve_options(&config, env::args().skip(3).collect()))
+		},
+		"hullsolve" | "unfoldsolve" => {
+			// solveproblem with just the one strategy, still taking the flags
+			let id = env::args().nth(2).unwrap().parse::<i64>().unwrap();
+			let (shape, skeleton) = load_problem(&config.data_dir, id).unwrap();
+			let mut args: Vec<String> = env::args().skip(3).filter(|a| a.starts_with("--")).collect();
+			args.push(if cmd == "hullsolve" { "hull" } else { "unfold" }.to_string());
+			solve_problem(&config, id, shape, skeleton, &solve_options(&config, args))
+		},
+		"batch" => {
+			// solves every problem without a perfect solution yet, on
+			// --workers=<n> threads (the config's workers by default), a minute
+			// each unless --time or the config says otherwise. Takes the same
+			// options as solveproblem.
+			let args: Vec<String> = env::args().skip(2).collect();
+			let workers = flag(&args, "workers").map_or(config.workers, |n| n as usize);
+			let mut options = solve_options(&config, args.into_iter().filter(|a| !a.starts_with("--workers=")).collect());
+			if options.time_limit.is_none() {
+				options.time_limit = Some(Duration::from_secs(60));
+			}
+			let start = std::time::Instant::now();
+			let reports = batch::run_batch(&config.data_dir, batch::problem_ids(&config.data_dir).unwrap(), workers, options);
+			batch::print_summary(&reports, start.elapsed());
+		},
+		"results" => {
+			// results best [id], results imperfect or results regressions
+			let results = results::Results::load(&results::results_path(&config.data_dir)).unwrap();
+			let show = |r: &results::Record| println!("Problem {}: {} by {}, resemblance {} (server {}), size {}, {}{}",
+				r.problem_id, r.hash, r.strategy, score(r.resemblance), score(r.server_resemblance), r.size, r.timestamp,
+				r.error.as_ref().map_or(String::new(), |e| format!(", rejected: {}", e)));
+			match env::args().nth(2).unwrap_or("best".to_string()).as_str() {
+				"best" => {
+					let only = env::args().nth(3).and_then(|id| id.parse::<i64>().ok());
+					for (_, r) in results.best().into_iter().filter(|&(id, _)| only.map_or(true, |only| only == id)) {
+						show(r);
+					}
+				},
+				"imperfect" => {
+					let imperfect = results.imperfect();
+					for r in imperfect.iter() {
+						show(r);
+					}
+					println!("{} problems not solved perfectly", imperfect.len());
+				},
+				"regressions" => {
+					for (best, last) in results.regressions() {
+						let kept = if last.saved { "" } else { " (not kept)" };
+						println!("Problem {} got worse: {} now{}, {} at best", best.problem_id, score(last.score()), kept, score(best.score()));
+						show(best);
+					}
+				},
+				query => {
+					println!("Unknown query {:?}, try best, imperfect or regressions", query);
+					process::exit(1);
+				}
+			}
+		},
+		"score" => {
+			// our score as of the last updatecontest, and the <n> problems (20
+			// by default) that solving perfectly would gain us most from
+			let contest = match sync::last_contest(&config.data_dir) {
+				Some(contest) => contest,
+				None => {
+					println!("No snapshot yet, run updatecontest first");
+					process::exit(1);
+				}
+			};
+			let results = results::Results::load(&results::results_path(&config.data_dir)).unwrap();
+			let board = scoring::Scoreboard::new(&contest, &results, &config.username);
+			println!("Score {:.2} as of {}, {:.2} of it for setting problems", board.total(), contest.snapshot_time, board.setter);
+			let count = env::args().nth(2).and_then(|n| n.parse::<usize>().ok()).unwrap_or(20);
+			for p in board.priorities().into_iter().take(count) {
+				println!("Problem {}: {:.2} to gain, {:.2} now with resemblance {}, worth {} with {} perfect solvers",
+					p.problem_id, p.gain(), p.score, score(p.ours), p.value, p.perfect_solvers);
+			}
+		},
+		"importlog" => {
+			// recovers our scores from the submissionlog, or another log
+			// given as the next argument. Importing twice adds nothing new.
+			let path = env::args().nth(2).unwrap_or("submissionlog".to_string());
+			let log = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+			let (entries, unmatched) = submissionlog::parse_log(log).unwrap();
+			let existing = results::Results::load(&results::results_path(&config.data_dir)).unwrap();
+			let (mut added, mut transient) = (0, 0);
+			for entry in entries.iter() {
+				if entry.transient() {
+					transient += 1;
+					continue;
+				}
+				let record = entry.record();
+				if !existing.known(&record) {
+					results::Results::append(&results::results_path(&config.data_dir), &record).unwrap();
+					added += 1;
+				}
+			}
+			println!("Imported {} of {} submissions, skipping {} rate limited or server errors and {} with no problem id",
+				added, entries.len(), transient, unmatched);
 		},
 		"submit" => {
-			restapi::submit(env::args().nth(2).unwrap().parse::<i64>().unwrap())
+			// any number of problems, sharing the rate limit. With --if-better
+			// only solutions that pass our checks and beat the server's best
+			// go, from every problem unless some are given, and --dry-run
+			// reports what --if-better would do without uploading anything.
+			let (flags, ids): (Vec<String>, Vec<String>) = env::args().skip(2).partition(|a| a.starts_with("--"));
+			let ids: Vec<i64> = ids.iter().map(|id| id.parse::<i64>().unwrap()).collect();
+			let dry_run = flags.iter().any(|f| f == "--dry-run");
+			if !dry_run {
+				need_key();
+			}
+			let failed = if dry_run || flags.iter().any(|f| f == "--if-better") {
+				let ids = if ids.is_empty() { batch::problem_ids(&config.data_dir).unwrap() } else { ids };
+				submit_better(&config, ids, dry_run)
+			} else {
+				let api = restapi::Api::new(&config);
+				let mut failed = Vec::new();
+				for id in ids {
+					match api.submit(id) {
+						Ok(reply) => println!("{}", reply),
+						Err(e) => {
+							println!("Submission of {} failed: {}", id, e);
+							failed.push(id);
+						}
+					}
+				}
+				failed
+			};
+			if !failed.is_empty() {
+				println!("Failed to submit {:?}", failed);
+				process::exit(1);
+			}
+		},
+		"generate" => {
+			// generate <out> [<fold file>] folds the unit square along each
+			// line of the file ("x1,y1 x2,y2", paper to the right going over),
+			// or without one along random lines. --seed=<n>, --folds=<n> and
+			// --denom=<n> steer the random folds. Writes <out>.solution.txt
+			// and the problem it sets, <out>.ourproblem.txt, ready to publish.
+			let (flags, args): (Vec<String>, Vec<String>) = env::args().skip(2).partition(|a| a.starts_with("--"));
+			let out = match args.first() {
+				Some(out) => out.trim_end_matches(".solution.txt").to_string(),
+				None => {
+					println!("Where to?");
+					process::exit(1);
+				}
+			};
+			let generated = match args.get(1) {
+				Some(path) => {
+					let mut text = String::new();
+					std::fs::File::open(path).and_then(|mut f| f.read_to_string(&mut text)).unwrap();
+					let folds: Vec<core::Line<BigRational>> = text.lines().filter(|l| !l.trim().is_empty())
+						.map(|l| l.trim().parse().unwrap()).collect();
+					match generator::generate(&folds) {
+						Ok(generated) => generated,
+						Err(e) => {
+							println!("Can't use those folds: {}", e);
+							process::exit(1);
+						}
+					}
+				},
+				None => {
+					let mut options = generator::RandomFolds::default();
+					options.seed = flag(&flags, "seed").unwrap_or(results::now());
+					options.folds = flag(&flags, "folds").map_or(options.folds, |n| n as usize);
+					options.denom = flag(&flags, "denom").map_or(options.denom, |n| n as i64);
+					println!("Folding at random with seed {}", options.seed);
+					generator::random(&options)
+				}
+			};
+			for (path, spec) in vec![(format!("{}.solution.txt", out), &generated.solution), (format!("{}.ourproblem.txt", out), &generated.problem)] {
+				std::fs::File::create(&path).and_then(|mut f| f.write_all(spec.as_bytes())).unwrap();
+			}
+			for line in generated.folds.iter() {
+				println!("Fold {} {}", line.p1, line.p2);
+			}
+			println!("{} folds, {} facets: solution size {}, problem size {}, in {}.solution.txt", generated.folds.len(), generated.facets,
+				core::spec_size(&generated.solution), core::spec_size(&generated.problem), out);
+		},
+		"publish" | "submitproblem" => {
+			// publish <solution file> [--at=<time>] [--dry-run] sets a problem,
+			// as does submitproblem <n> with custom/NNNNN.solution.txt
+			let (flags, args): (Vec<String>, Vec<String>) = env::args().skip(2).partition(|a| a.starts_with("--"));
+			let path = match (cmd.trim(), args.first()) {
+				("publish", Some(path)) => path.clone(),
+				(_, Some(id)) => format!("{}/custom/{:05}.solution.txt", config.data_dir, id.parse::<i64>().unwrap()),
+				_ => {
+					println!("Which solution?");
+					process::exit(1);
+				}
+			};
+			let dry_run = flags.iter().any(|f| f == "--dry-run");
+			if !dry_run {
+				need_key();
+			}
+			if let Err(e) = publish_solution(&config, &path, flag(&flags, "at").map(|t| t as i64), dry_run) {
+				println!("Not published: {}", e);
+				process::exit(1);
+			}
 		},
-		"submitproblem" => {
-			restapi::submit_problem(env::args().nth(2).unwrap().parse::<i64>().unwrap())
+		"mockserver" => {
+			// serves the data directory as the contest on the given port (8016
+			// by default), for trying the other commands offline against
+			// SUPERLEGIT_BASE_URL=http://127.0.0.1:8016/api
+			let port = env::args().nth(2).and_then(|p| p.parse::<u16>().ok()).unwrap_or(8016);
+			let server = mockserver::MockServer::load(&config.data_dir).unwrap();
+			let listener = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+			println!("Serving {} at http://127.0.0.1:{}/api", config.data_dir, port);
+			server.run(listener).unwrap();
 		},
 		_ => {
 			println!("{:?}", help_string);
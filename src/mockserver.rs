@@ -0,0 +1,329 @@
+// A stand-in for the contest server, so the whole download, solve and submit
+// round trip can be tried without a network. It serves the problems in a
+// directory as one snapshot and judges submissions with our own validator and
+// resemblance. Anything submitted is only kept in memory.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::{BufRead,BufReader,Read,Write};
+use std::net::{TcpListener,TcpStream};
+
+use num::rational::BigRational;
+use rustc_serialize::json::{Json,ToJson};
+
+use core;
+use parse;
+use publish;
+use results::{now,spec_hash};
+
+fn object(fields: Vec<(&str, Json)>) -> Json {
+	Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn error(why: &str) -> Json {
+	object(vec![("ok", false.to_json()), ("error", why.to_json())])
+}
+
+struct Problem {
+	hash: String,
+	size: usize,
+	publish_time: i64,
+	owner: String,
+	solution_size: usize,
+	// our best resemblance and the size it took
+	best: Option<(f64, usize)>,
+}
+
+pub struct MockServer {
+	problems: BTreeMap<i64, Problem>,
+	blobs: BTreeMap<String, Vec<u8>>,
+	// times and hashes, oldest first
+	snapshots: Vec<(i64, String)>,
+}
+
+impl MockServer {
+	// Every NNNNN.problem.txt in data_dir
+	pub fn load(data_dir: &str) -> io::Result<MockServer> {
+		let mut server = MockServer{problems: BTreeMap::new(), blobs: BTreeMap::new(), snapshots: Vec::new()};
+		for entry in try!(fs::read_dir(data_dir)) {
+			let path = try!(entry).path();
+			let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or(String::new());
+			if let Some(id) = name.split('.').next().and_then(|id| id.parse::<i64>().ok()).filter(|_| name.ends_with(".problem.txt")) {
+				let mut spec = Vec::new();
+				try!(try!(fs::File::open(&path)).read_to_end(&mut spec));
+				server.publish(id, spec, 0, "0", 0);
+			}
+		}
+		server.snapshot();
+		Ok(server)
+	}
+
+	fn publish(&mut self, id: i64, spec: Vec<u8>, publish_time: i64, owner: &str, solution_size: usize) {
+		let hash = spec_hash(&String::from_utf8_lossy(&spec));
+		self.problems.insert(id, Problem{
+			hash: hash.clone(), size: core::spec_size(&spec), publish_time: publish_time, owner: owner.to_string(),
+			solution_size: solution_size, best: None
+		});
+		self.blobs.insert(hash, spec);
+	}
+
+	// Records the contest as it stands now
+	fn snapshot(&mut self) {
+		let problems = self.problems.iter().map(|(&id, p)| object(vec![
+			("problem_id", id.to_json()),
+			("publish_time", p.publish_time.to_json()),
+			("owner", p.owner.to_json()),
+			("problem_size", p.size.to_json()),
+			("solution_size", p.solution_size.to_json()),
+			("problem_spec_hash", p.hash.to_json()),
+			("ranking", Json::Array(p.best.iter().map(|&(resemblance, size)| object(vec![
+				("resemblance", resemblance.to_json()), ("solution_size", size.to_json())
+			])).collect())),
+		])).collect();
+		let time = now() as i64;
+		let contest = object(vec![
+			("snapshot_time", time.to_json()),
+			("problems", Json::Array(problems)),
+			("leaderboard", Json::Array(Vec::new())),
+			("users", Json::Array(Vec::new())),
+		]).to_string();
+		let hash = spec_hash(&contest);
+		self.blobs.insert(hash.clone(), contest.into_bytes());
+		self.snapshots.push((time, hash));
+	}
+
+	fn submit_solution(&mut self, form: &BTreeMap<String, Vec<u8>>) -> (u16, Json) {
+		let (id, spec) = match (form.get("problem_id").and_then(|id| String::from_utf8_lossy(id).trim().parse::<i64>().ok()), form.get("solution_spec")) {
+			(Some(id), Some(spec)) => (id, spec),
+			_ => return (400, error("Expected problem_id and solution_spec"))
+		};
+		let problem = match self.problems.get_mut(&id) {
+			Some(problem) => problem,
+			None => return (200, error(&format!("Problem {} doesn't exist", id)))
+		};
+		let solution = match core::judge(&String::from_utf8_lossy(spec)) {
+			Ok(solution) => solution,
+			Err(why) => return (200, error(&why))
+		};
+		let (shape, _) = match parse::parse::<BigRational, &[u8]>(&self.blobs[&problem.hash]) {
+			Ok(problem) => problem,
+			Err(e) => return (500, error(&format!("Can't read problem {}: {:?}", id, e)))
+		};
+		let resemblance = core::resemblance(&shape, &solution.dst_polys());
+		let better = problem.best.map_or(true, |(best, best_size)| resemblance > best || (resemblance == best && core::spec_size(spec) < best_size));
+		if better {
+			problem.best = Some((resemblance, core::spec_size(spec)));
+		}
+		(200, object(vec![
+			("ok", true.to_json()),
+			("problem_id", id.to_json()),
+			("resemblance", resemblance.to_json()),
+			("solution_spec_hash", spec_hash(&String::from_utf8_lossy(spec)).to_json()),
+			("solution_size", core::spec_size(spec).to_json()),
+		]))
+	}
+
+	fn submit_problem(&mut self, form: &BTreeMap<String, Vec<u8>>) -> (u16, Json) {
+		let spec = match form.get("solution_spec") {
+			Some(spec) => spec.clone(),
+			None => return (400, error("Expected solution_spec"))
+		};
+		let solution = match core::judge(&String::from_utf8_lossy(&spec)) {
+			Ok(solution) => solution,
+			Err(why) => return (200, error(&why))
+		};
+		let publish_time = form.get("publish_time").and_then(|t| String::from_utf8_lossy(t).trim().parse::<i64>().ok()).unwrap_or(now() as i64);
+		let id = self.problems.keys().next_back().map_or(1, |id| id + 1);
+		let problem = publish::problem_spec(&solution).into_bytes();
+		let (problem_size, problem_hash) = (core::spec_size(&problem), spec_hash(&String::from_utf8_lossy(&problem)));
+		self.publish(id, problem, publish_time, "0", core::spec_size(&spec));
+		self.snapshot();
+		(200, object(vec![
+			("ok", true.to_json()),
+			("problem_id", id.to_json()),
+			("publish_time", publish_time.to_json()),
+			("solution_spec_hash", spec_hash(&String::from_utf8_lossy(&spec)).to_json()),
+			("solution_size", core::spec_size(&spec).to_json()),
+			("problem_spec_hash", problem_hash.to_json()),
+			("problem_size", problem_size.to_json()),
+		]))
+	}
+
+	// The status and body for a request. Paths may or may not start with /api.
+	pub fn handle(&mut self, method: &str, path: &str, api_key: Option<&str>, form: &BTreeMap<String, Vec<u8>>) -> (u16, Vec<u8>) {
+		if api_key.map_or(true, |key| key.trim().is_empty()) {
+			return (403, error("Missing API key").to_string().into_bytes());
+		}
+		let path = path.trim_start_matches("/api").trim_start_matches('/');
+		let (status, reply) = match (method, path) {
+			("GET", "snapshot/list") => (200, object(vec![
+				("ok", true.to_json()),
+				("snapshots", Json::Array(self.snapshots.iter().map(|&(time, ref hash)| object(vec![
+					("snapshot_time", time.to_json()), ("snapshot_hash", hash.to_json())
+				])).collect()))
+			])),
+			("GET", blob) if blob.starts_with("blob/") => {
+				return match self.blobs.get(&blob[5..]) {
+					Some(data) => (200, data.clone()),
+					None => (404, error("No such blob").to_string().into_bytes())
+				};
+			},
+			("POST", "solution/submit") => self.submit_solution(form),
+			("POST", "problem/submit") => self.submit_problem(form),
+			_ => (404, error(&format!("No {} {}", method, path)))
+		};
+		(status, reply.to_string().into_bytes())
+	}
+
+	// Answers requests one at a time, forever
+	pub fn run(mut self, listener: TcpListener) -> io::Result<()> {
+		for stream in listener.incoming() {
+			let mut stream = try!(stream);
+			if let Err(e) = self.answer(&mut stream) {
+				println!("Bad request: {}", e);
+			}
+		}
+		Ok(())
+	}
+
+	fn answer(&mut self, stream: &mut TcpStream) -> io::Result<()> {
+		let (method, path, headers, body) = {
+			let mut reader = BufReader::new(&mut *stream);
+			let mut line = String::new();
+			try!(reader.read_line(&mut line));
+			let fields: Vec<String> = line.split_whitespace().map(|f| f.to_string()).collect();
+			if fields.len() < 2 {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("request line {:?}", line)));
+			}
+			let mut headers = BTreeMap::new();
+			loop {
+				let mut line = String::new();
+				if try!(reader.read_line(&mut line)) == 0 || line.trim().is_empty() {
+					break;
+				}
+				if let Some(i) = line.find(':') {
+					headers.insert(line[..i].trim().to_lowercase(), line[i+1..].trim().to_string());
+				}
+			}
+			let length = headers.get("content-length").and_then(|l| l.parse::<usize>().ok()).unwrap_or(0);
+			let mut body = vec![0; length];
+			try!(reader.read_exact(&mut body));
+			(fields[0].clone(), fields[1].clone(), headers, body)
+		};
+		let form = headers.get("content-type").map_or(BTreeMap::new(), |ct| multipart(ct, &body));
+		let (status, reply) = self.handle(&method, &path, headers.get("x-api-key").map(|k| k.as_str()), &form);
+		println!("{} {} -> {}", method, path, status);
+		let reason = match status { 200 => "OK", 400 => "Bad Request", 403 => "Forbidden", 404 => "Not Found", _ => "Error" };
+		try!(write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, reason, reply.len()));
+		stream.write_all(&reply)
+	}
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+	if from > haystack.len() {
+		return None;
+	}
+	haystack[from..].windows(needle.len()).position(|w| w == needle).map(|i| i + from)
+}
+
+// The fields of a multipart/form-data body, by name
+fn multipart(content_type: &str, body: &[u8]) -> BTreeMap<String, Vec<u8>> {
+	let mut form = BTreeMap::new();
+	let boundary = match content_type.split("boundary=").nth(1) {
+		Some(boundary) => format!("--{}", boundary.trim_matches('"')).into_bytes(),
+		None => return form
+	};
+	let mut start = match find(body, &boundary, 0) {
+		Some(i) => i + boundary.len(),
+		None => return form
+	};
+	while let Some(end) = find(body, &boundary, start) {
+		// each part is CRLF, headers, a blank line, the data and another CRLF
+		let part = &body[start..end];
+		start = end + boundary.len();
+		let header_end = match find(part, b"\r\n\r\n", 0) {
+			Some(i) => i,
+			None => continue
+		};
+		let headers = String::from_utf8_lossy(&part[..header_end]);
+		let name = headers.split("name=\"").nth(1).and_then(|n| n.split('"').next());
+		if let Some(name) = name {
+			let data = &part[header_end + 4..];
+			let data = if data.ends_with(b"\r\n") { &data[..data.len() - 2] } else { data };
+			form.insert(name.to_string(), data.to_vec());
+		}
+	}
+	form
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::env;
+	use std::fs;
+	use std::io::Write;
+	use std::net::TcpListener;
+	use std::thread;
+	use std::time::Duration;
+
+	use config::Config;
+	use http::HttpError;
+	use restapi::Api;
+
+	fn write(path: &str, data: &str) {
+		fs::File::create(path).unwrap().write_all(data.as_bytes()).unwrap();
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let root = env::temp_dir().join(format!("superlegit-mock-{}", ::std::process::id()));
+		let (served, local) = (root.join("served"), root.join("local"));
+		fs::create_dir_all(&served).unwrap();
+		fs::create_dir_all(local.join("custom")).unwrap();
+		let (served, local) = (served.to_string_lossy().into_owned(), local.to_string_lossy().into_owned());
+		write(&format!("{}/00001.problem.txt", served), "1\n4\n0,0\n1,0\n1,1\n0,1\n4\n0,0 1,0\n1,0 1,1\n1,1 0,1\n0,1 0,0\n");
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let server = MockServer::load(&served).unwrap();
+		thread::spawn(move || server.run(listener));
+
+		let mut config = Config::default();
+		config.base_url = format!("http://127.0.0.1:{}/api", port);
+		config.api_key = "test".to_string();
+		config.data_dir = local.clone();
+		config.request_interval = Duration::from_millis(0);
+		let api = Api::new(&config);
+
+		let contest = api.get_contest_meta().unwrap();
+		assert_eq!(1, contest.problems.len());
+		assert!(api.save_problems(&contest.problems).is_empty());
+		assert!(fs::metadata(format!("{}/00001.problem.txt", local)).is_ok());
+
+		// the paper as it comes is a perfect answer
+		write(&format!("{}/00001.solution.txt", local), "4\n0,0\n1,0\n1,1\n0,1\n1\n4 0 1 2 3\n0,0\n1,0\n1,1\n0,1\n");
+		let reply = api.submit(1).unwrap();
+		assert!(reply.ok);
+		assert_eq!(Some(1.0), reply.resemblance);
+		assert!(fs::metadata(format!("{}/00001.submission", local)).is_ok());
+
+		write(&format!("{}/00001.solution.txt", local), "4\n0,0\n2,0\n1,1\n0,1\n1\n4 0 1 2 3\n0,0\n1,0\n1,1\n0,1\n");
+		let reply = api.submit(1).unwrap();
+		assert!(!reply.ok);
+		assert!(reply.error.unwrap().contains("outside the unit square"));
+
+		// folded in half
+		write(&format!("{}/custom/00002.solution.txt", local), "6\n0,0\n1/2,0\n1,0\n0,1\n1/2,1\n1,1\n2\n4 0 1 4 3\n4 1 2 5 4\n0,0\n1/2,0\n0,0\n0,1\n1/2,1\n0,1\n");
+		let reply = api.submit_problem(&format!("{}/custom/00002.solution.txt", local), 1470603600).unwrap();
+		assert!(reply.ok);
+		assert_eq!(Some(2), reply.problem_id);
+
+		let mut anonymous = config.clone();
+		anonymous.api_key = String::new();
+		match Api::new(&anonymous).submit(1) {
+			Err(HttpError::Status(403, _)) => {},
+			other => panic!("expected a 403, got {:?}", other)
+		}
+		fs::remove_dir_all(&root).unwrap();
+	}
+}
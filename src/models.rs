@@ -0,0 +1,249 @@
+// What the contest API sends back, decoded from JSON. A reply missing
+// something we need is a DecodeError naming it, never a panic.
+use std::fmt;
+
+use rustc_serialize::json::Json;
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct DecodeError(pub String);
+
+impl fmt::Display for DecodeError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Can't decode reply: {}", self.0)
+	}
+}
+
+pub trait FromJson: Sized {
+	fn from_json(json: &Json) -> Result<Self, DecodeError>;
+}
+
+pub fn decode<T: FromJson>(data: &str) -> Result<T, DecodeError> {
+	let json = try!(Json::from_str(data).map_err(|e| DecodeError(format!("{}", e))));
+	T::from_json(&json)
+}
+
+fn field<'j>(json: &'j Json, name: &str) -> Result<&'j Json, DecodeError> {
+	json.find(name).ok_or(DecodeError(format!("no {}", name)))
+}
+
+fn string(json: &Json, name: &str) -> Result<String, DecodeError> {
+	let value = try!(field(json, name));
+	// ids sometimes come as strings and sometimes as numbers
+	match *value {
+		Json::String(ref s) => Ok(s.clone()),
+		Json::I64(_) | Json::U64(_) => Ok(value.to_string()),
+		_ => Err(DecodeError(format!("{} isn't a string", name)))
+	}
+}
+
+fn int(json: &Json, name: &str) -> Result<i64, DecodeError> {
+	try!(field(json, name)).as_i64().ok_or(DecodeError(format!("{} isn't a whole number", name)))
+}
+
+fn float(json: &Json, name: &str) -> Result<f64, DecodeError> {
+	try!(field(json, name)).as_f64().ok_or(DecodeError(format!("{} isn't a number", name)))
+}
+
+// Each item of the named array, saying which one went wrong
+fn list<T: FromJson>(json: &Json, name: &str) -> Result<Vec<T>, DecodeError> {
+	let items = try!(try!(field(json, name)).as_array().ok_or(DecodeError(format!("{} isn't a list", name))));
+	let mut out = Vec::new();
+	for (i, item) in items.iter().enumerate() {
+		out.push(try!(T::from_json(item).map_err(|e| DecodeError(format!("{}[{}]: {}", name, i, e.0)))));
+	}
+	Ok(out)
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Snapshot {
+	pub snapshot_time: i64,
+	pub snapshot_hash: String,
+}
+
+impl FromJson for Snapshot {
+	fn from_json(json: &Json) -> Result<Snapshot, DecodeError> {
+		Ok(Snapshot{snapshot_time: try!(int(json, "snapshot_time")), snapshot_hash: try!(string(json, "snapshot_hash"))})
+	}
+}
+
+// snapshot/list
+#[derive(Debug,Clone,PartialEq)]
+pub struct SnapshotList {
+	pub snapshots: Vec<Snapshot>,
+}
+
+impl SnapshotList {
+	pub fn latest(&self) -> Option<&Snapshot> {
+		self.snapshots.iter().max_by_key(|s| s.snapshot_time)
+	}
+}
+
+impl FromJson for SnapshotList {
+	fn from_json(json: &Json) -> Result<SnapshotList, DecodeError> {
+		Ok(SnapshotList{snapshots: try!(list(json, "snapshots"))})
+	}
+}
+
+// One team's best go at a problem
+#[derive(Debug,Clone,PartialEq)]
+pub struct Ranking {
+	pub resemblance: f64,
+	pub solution_size: usize,
+}
+
+impl FromJson for Ranking {
+	fn from_json(json: &Json) -> Result<Ranking, DecodeError> {
+		Ok(Ranking{resemblance: try!(float(json, "resemblance")), solution_size: try!(int(json, "solution_size")) as usize})
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct ProblemMeta {
+	pub problem_id: i64,
+	pub publish_time: i64,
+	// username of the team that set it
+	pub owner: String,
+	pub problem_size: usize,
+	pub solution_size: usize,
+	pub problem_spec_hash: String,
+	pub ranking: Vec<Ranking>,
+}
+
+impl FromJson for ProblemMeta {
+	fn from_json(json: &Json) -> Result<ProblemMeta, DecodeError> {
+		Ok(ProblemMeta{
+			problem_id: try!(int(json, "problem_id")),
+			publish_time: try!(int(json, "publish_time")),
+			owner: try!(string(json, "owner")),
+			problem_size: try!(int(json, "problem_size")) as usize,
+			solution_size: try!(int(json, "solution_size")) as usize,
+			problem_spec_hash: try!(string(json, "problem_spec_hash")),
+			ranking: try!(list(json, "ranking")),
+		})
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Leader {
+	pub username: String,
+	pub score: f64,
+}
+
+impl FromJson for Leader {
+	fn from_json(json: &Json) -> Result<Leader, DecodeError> {
+		Ok(Leader{username: try!(string(json, "username")), score: try!(float(json, "score"))})
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct User {
+	pub username: String,
+	pub display_name: String,
+}
+
+impl FromJson for User {
+	fn from_json(json: &Json) -> Result<User, DecodeError> {
+		Ok(User{username: try!(string(json, "username")), display_name: try!(string(json, "display_name"))})
+	}
+}
+
+// The blob a snapshot hash points at
+#[derive(Debug,Clone,PartialEq)]
+pub struct Contest {
+	pub snapshot_time: i64,
+	pub problems: Vec<ProblemMeta>,
+	pub leaderboard: Vec<Leader>,
+	pub users: Vec<User>,
+}
+
+impl FromJson for Contest {
+	fn from_json(json: &Json) -> Result<Contest, DecodeError> {
+		Ok(Contest{
+			snapshot_time: try!(int(json, "snapshot_time")),
+			problems: try!(list(json, "problems")),
+			leaderboard: try!(list(json, "leaderboard")),
+			users: try!(list(json, "users")),
+		})
+	}
+}
+
+// solution/submit and problem/submit. Which fields are there depends on the
+// endpoint and whether it went through, so only ok is certain.
+#[derive(Debug,Clone,PartialEq)]
+pub struct SubmitResponse {
+	pub ok: bool,
+	pub problem_id: Option<i64>,
+	pub resemblance: Option<f64>,
+	pub solution_size: Option<usize>,
+	pub solution_spec_hash: Option<String>,
+	// problem/submit only
+	pub publish_time: Option<i64>,
+	pub problem_size: Option<usize>,
+	pub problem_spec_hash: Option<String>,
+	pub error: Option<String>,
+}
+
+impl FromJson for SubmitResponse {
+	fn from_json(json: &Json) -> Result<SubmitResponse, DecodeError> {
+		Ok(SubmitResponse{
+			ok: try!(try!(field(json, "ok")).as_boolean().ok_or(DecodeError("ok isn't true or false".to_string()))),
+			problem_id: int(json, "problem_id").ok(),
+			resemblance: float(json, "resemblance").ok(),
+			solution_size: int(json, "solution_size").ok().map(|s| s as usize),
+			solution_spec_hash: string(json, "solution_spec_hash").ok(),
+			publish_time: int(json, "publish_time").ok(),
+			problem_size: int(json, "problem_size").ok().map(|s| s as usize),
+			problem_spec_hash: string(json, "problem_spec_hash").ok(),
+			error: string(json, "error").ok(),
+		})
+	}
+}
+
+impl fmt::Display for SubmitResponse {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let id = self.problem_id.map_or("?".to_string(), |id| id.to_string());
+		match (self.ok, &self.error) {
+			(true, _) if self.problem_spec_hash.is_some() => write!(f, "Problem {} published at {}, size {}", id,
+				self.publish_time.map_or("-".to_string(), |t| t.to_string()),
+				self.problem_size.map_or("-".to_string(), |s| s.to_string())),
+			(true, _) => write!(f, "Problem {} accepted: resemblance {}, size {}, hash {}", id,
+				self.resemblance.map_or("-".to_string(), |r| r.to_string()),
+				self.solution_size.map_or("-".to_string(), |s| s.to_string()),
+				self.solution_spec_hash.as_ref().map_or("-", |h| h.as_str())),
+			(false, &Some(ref e)) => write!(f, "Problem {} rejected: {}", id, e),
+			(false, &None) => write!(f, "Problem {} rejected", id)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_decode() {
+		let list: SnapshotList = decode(r#"{"ok":true,"snapshots":[{"snapshot_hash":"89c4","snapshot_time":1470322800},{"snapshot_hash":"055e","snapshot_time":1470326400}]}"#).unwrap();
+		assert_eq!("055e", list.latest().unwrap().snapshot_hash);
+
+		let contest: Contest = decode(r#"{"snapshot_time":1470326400,
+			"problems":[{"problem_id":1,"publish_time":1470322800,"owner":"1","problem_size":94,"solution_size":85,
+				"problem_spec_hash":"77e8","ranking":[{"resemblance":1.0,"solution_size":85}]}],
+			"leaderboard":[{"username":"42","score":3.5}],
+			"users":[{"username":"42","display_name":"Team Super Legit"}]}"#).unwrap();
+		assert_eq!(1, contest.problems[0].problem_id);
+		assert_eq!("1", contest.problems[0].owner);
+		assert_eq!(vec![Ranking{resemblance: 1.0, solution_size: 85}], contest.problems[0].ranking);
+		assert_eq!("Team Super Legit", contest.users[0].display_name);
+
+		// missing fields are errors that say where
+		let bad = decode::<Contest>(r#"{"snapshot_time":1,"problems":[{"problem_id":1}],"leaderboard":[],"users":[]}"#);
+		assert_eq!(Err(DecodeError("problems[0]: no publish_time".to_string())), bad);
+		assert!(decode::<SnapshotList>("<html>").is_err());
+
+		let reply: SubmitResponse = decode(r#"{"ok":true,"problem_id":4,"resemblance":0.5,"solution_size":60,"solution_spec_hash":"feb7"}"#).unwrap();
+		assert_eq!((Some(4), Some(0.5), Some(60)), (reply.problem_id, reply.resemblance, reply.solution_size));
+		let reply: SubmitResponse = decode(r#"{"ok":false,"error":"Invalid solution spec"}"#).unwrap();
+		assert_eq!("Problem ? rejected: Invalid solution spec", reply.to_string());
+		assert!(decode::<SubmitResponse>(r#"{"problem_id":4}"#).is_err());
+	}
+}
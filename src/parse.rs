@@ -11,6 +11,7 @@ pub use core::*;
 pub enum ParseError {
 	BadPoint,
 	BadLine,
+	BadFacet,
 	IOError,
 	SubError,
 }
@@ -74,6 +75,32 @@ pub fn parse<N: Num, R: Read>(stream: R) -> Result<(Shape<N>, Skeleton<N>), Pars
 	Ok((Shape::new(shape), skel))
 }
 
+// Reads a solution back in, eg. to check it before submitting
+pub fn parse_solution<N: Num, R: Read>(stream: R) -> Result<SolutionSpec<N>, ParseError> {
+	let mut reader = BufReader::new(stream);
+	let num_points: usize = try!(parse_line(&mut reader));
+	let mut src = Vec::new();
+	for _ in 0..num_points {
+		src.push(try!(parse_line::<Point<N>,R>(&mut reader)));
+	}
+	let num_facets: usize = try!(parse_line(&mut reader));
+	let mut facets = Vec::new();
+	for _ in 0..num_facets {
+		let mut s = String::new();
+		try!(reader.read_line(&mut s));
+		let fields: Vec<usize> = try!(ParseError::wrap(s.split_whitespace().map(|f| f.parse::<usize>()).collect()));
+		if fields.len() == 0 || fields[0] != fields.len() - 1 {
+			return Err(ParseError::BadFacet);
+		}
+		facets.push(fields[1..].to_vec());
+	}
+	let mut dst = Vec::new();
+	for _ in 0..num_points {
+		dst.push(try!(parse_line::<Point<N>,R>(&mut reader)));
+	}
+	Ok(SolutionSpec{src: src, facets: facets, dst: dst})
+}
+
 fn parse_line <T: FromStr+Debug, R: Read>(reader: &mut BufReader<R>) -> Result<T, ParseError> where <T as FromStr>::Err: Debug {
 	let mut s = String::new();
 	try!(reader.read_line(&mut s));
@@ -116,7 +143,7 @@ mod tests {
 		let (shape, skel) = parse::<BigRational, File>(f).unwrap();
 		assert_eq!(1, shape.polys.len());
 		assert_eq!(4, shape.polys[0].points.len());
-		assert_eq!(1.0, shape.polys[0].area());
+		assert_eq!(rati(1, 1), shape.polys[0].signed_area().abs());
 		assert_eq!(4, skel.len());
 		assert_eq!(Point{x: rati(1, 1), y: rati(0, 1)}, &shape.polys[0].points[1] - &shape.polys[0].points[0]);
 		assert!(!shape.polys[0].is_hole());
@@ -133,6 +160,16 @@ mod tests {
 		//assert_eq!(0,1);
 	}
 
+	#[test]
+	fn test_parse_solution() {
+		let spec = "4\n0,0\n1,0\n1,1\n0,1\n1\n4 0 1 2 3\n0,0\n1,0\n1,1\n0,1\n";
+		let solution = parse_solution::<BigRational, &[u8]>(spec.as_bytes()).unwrap();
+		assert_eq!(4, solution.src.len());
+		assert_eq!(vec![vec![0, 1, 2, 3]], solution.facets);
+		assert!(solution.validate().is_ok());
+		assert!(parse_solution::<BigRational, &[u8]>("4\n0,0\n1,0\n1,1\n0,1\n1\n3 0 1 2 3\n".as_bytes()).is_err());
+	}
+
 	#[test]
 	fn test_float() {
 		assert_eq!(0.5f64, "4328029871649615121465353437184/8656059743299229793415925725865".parse::<BigRational>().unwrap().to_f64());
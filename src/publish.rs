@@ -0,0 +1,217 @@
+// Setting problems for the other teams. A solution of ours is checked and the
+// problem it sets worked out locally before anything is uploaded, and each
+// problem the server accepts is kept in published.jsonl in the data directory.
+use std::collections::{BTreeMap,BTreeSet};
+use std::io;
+
+use num::rational::BigRational;
+use rustc_serialize::json::{Json,ToJson};
+
+use core;
+use jsonl;
+use models::SubmitResponse;
+use results::{now,spec_hash};
+
+// problems bigger than this are rejected, like solutions
+pub const MAX_PROBLEM_SIZE: usize = 5000;
+
+// Problems go public on the hour, from 2016-08-06 00:00 to 2016-08-07 21:00
+// UTC, and a team gets one problem in each slot
+pub const FIRST_SLOT: i64 = 1470441600;
+pub const LAST_SLOT: i64 = 1470603600;
+const SLOT: i64 = 3600;
+
+pub fn published_path(data_dir: &str) -> String {
+	format!("{}/published.jsonl", data_dir)
+}
+
+// A problem spec for the silhouette and skeleton
+pub fn write_problem(shape: &core::Shape<BigRational>, skeleton: &core::Skeleton<BigRational>) -> String {
+	let mut out = format!("{}\n", shape.polys.len());
+	for poly in shape.polys.iter() {
+		out.push_str(&format!("{}\n", poly.points.len()));
+		for p in poly.points.iter() {
+			out.push_str(&format!("{}\n", p));
+		}
+	}
+	out.push_str(&format!("{}\n", skeleton.lines.len()));
+	for line in skeleton.lines.iter() {
+		out.push_str(&format!("{} {}\n", line.p1, line.p2));
+	}
+	out
+}
+
+// The problem a solution sets: the outline of the folded paper and every
+// crease. The server may write it differently, so its hash needn't match.
+pub fn problem_spec(solution: &core::SolutionSpec<BigRational>) -> String {
+	let polys = solution.dst_polys();
+	write_problem(&core::silhouette(&polys), &core::creases(&polys))
+}
+
+// A solution that's fit to publish, with the problem it sets
+#[derive(Debug,Clone)]
+pub struct Prepared {
+	pub solution_size: usize,
+	pub solution_hash: String,
+	pub problem: String,
+	pub problem_size: usize,
+	pub problem_hash: String,
+}
+
+pub fn prepare(solution: &str) -> Result<Prepared, String> {
+	let parsed = try!(core::judge(solution));
+	let problem = problem_spec(&parsed);
+	let problem_size = core::spec_size(&problem);
+	if problem_size > MAX_PROBLEM_SIZE {
+		return Err(format!("Problem size {} is over the limit of {}", problem_size, MAX_PROBLEM_SIZE));
+	}
+	Ok(Prepared{
+		solution_size: core::spec_size(solution),
+		solution_hash: spec_hash(solution),
+		problem_size: problem_size,
+		problem_hash: spec_hash(&problem),
+		problem: problem,
+	})
+}
+
+// The first slot after `now` that we haven't used yet
+pub fn next_slot(now: i64, taken: &BTreeSet<i64>) -> Option<i64> {
+	let first = if now < FIRST_SLOT { FIRST_SLOT } else { (now / SLOT + 1) * SLOT };
+	(0..).map(|n| first + n * SLOT).take_while(|&t| t <= LAST_SLOT).find(|t| !taken.contains(t))
+}
+
+// The publish time asked for if it's one we can have, otherwise the next free slot
+pub fn choose_time(requested: Option<i64>, now: i64, taken: &BTreeSet<i64>) -> Result<i64, String> {
+	match requested {
+		Some(t) if t < FIRST_SLOT || t > LAST_SLOT || t % SLOT != 0 =>
+			Err(format!("{} isn't on the hour between {} and {}", t, FIRST_SLOT, LAST_SLOT)),
+		Some(t) if t <= now => Err(format!("{} has gone already", t)),
+		Some(t) if taken.contains(&t) => Err(format!("We already publish a problem at {}", t)),
+		Some(t) => Ok(t),
+		None => next_slot(now, taken).ok_or("No publish slots left".to_string())
+	}
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Publication {
+	// the server's id for the problem
+	pub problem_id: i64,
+	pub publish_time: i64,
+	pub solution_hash: String,
+	pub solution_size: usize,
+	// as the server has it
+	pub problem_hash: String,
+	pub problem_size: usize,
+	// seconds since the epoch when we submitted it
+	pub timestamp: u64,
+}
+
+impl Publication {
+	// From the server's reply, falling back on what we worked out ourselves
+	pub fn new(reply: &SubmitResponse, prepared: &Prepared, publish_time: i64) -> Option<Publication> {
+		Some(Publication{
+			problem_id: match reply.problem_id { Some(id) => id, None => return None },
+			publish_time: reply.publish_time.unwrap_or(publish_time),
+			solution_hash: reply.solution_spec_hash.clone().unwrap_or(prepared.solution_hash.clone()),
+			solution_size: reply.solution_size.unwrap_or(prepared.solution_size),
+			problem_hash: reply.problem_spec_hash.clone().unwrap_or(prepared.problem_hash.clone()),
+			problem_size: reply.problem_size.unwrap_or(prepared.problem_size),
+			timestamp: now(),
+		})
+	}
+
+	pub fn from_json(json: &Json) -> Option<Publication> {
+		let int = |name: &str| json.find(name).and_then(|v| v.as_i64());
+		let string = |name: &str| json.find(name).and_then(|v| v.as_string()).unwrap_or("").to_string();
+		Some(Publication{
+			problem_id: match int("problem_id") { Some(id) => id, None => return None },
+			publish_time: match int("publish_time") { Some(t) => t, None => return None },
+			solution_hash: string("solution_hash"),
+			solution_size: int("solution_size").unwrap_or(0) as usize,
+			problem_hash: string("problem_hash"),
+			problem_size: int("problem_size").unwrap_or(0) as usize,
+			timestamp: int("timestamp").unwrap_or(0) as u64,
+		})
+	}
+}
+
+impl ToJson for Publication {
+	fn to_json(&self) -> Json {
+		let mut obj = BTreeMap::new();
+		obj.insert("problem_id".to_string(), self.problem_id.to_json());
+		obj.insert("publish_time".to_string(), self.publish_time.to_json());
+		obj.insert("solution_hash".to_string(), self.solution_hash.to_json());
+		obj.insert("solution_size".to_string(), self.solution_size.to_json());
+		obj.insert("problem_hash".to_string(), self.problem_hash.to_json());
+		obj.insert("problem_size".to_string(), self.problem_size.to_json());
+		obj.insert("timestamp".to_string(), self.timestamp.to_json());
+		Json::Object(obj)
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Publications {
+	pub records: Vec<Publication>,
+}
+
+impl Publications {
+	pub fn load(path: &str) -> io::Result<Publications> {
+		Ok(Publications{records: try!(jsonl::load(path, "publication", Publication::from_json))})
+	}
+
+	pub fn append(path: &str, record: &Publication) -> io::Result<()> {
+		jsonl::append(path, record)
+	}
+
+	pub fn slots(&self) -> BTreeSet<i64> {
+		self.records.iter().map(|r| r.publish_time).collect()
+	}
+
+	// The problem we already set with this very solution
+	pub fn find(&self, solution_hash: &str) -> Option<&Publication> {
+		self.records.iter().find(|r| r.solution_hash == solution_hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::BTreeSet;
+	use rustc_serialize::json::{Json,ToJson};
+
+	#[test]
+	fn test_prepare() {
+		// folded in half, the two layers showing as one
+		let prepared = prepare("6\n0,0\n1/2,0\n1,0\n0,1\n1/2,1\n1,1\n2\n4 0 1 4 3\n4 1 2 5 4\n0,0\n1/2,0\n0,0\n0,1\n1/2,1\n0,1\n").unwrap();
+		assert_eq!("1\n4\n0,0\n1/2,0\n1/2,1\n0,1\n4\n0,1 0,0\n0,1 1/2,1\n0,0 1/2,0\n1/2,0 1/2,1\n", prepared.problem);
+		assert_eq!(prepared.problem_size, core::spec_size(&prepared.problem));
+		assert!(prepare("4\n0,0\n2,0\n1,1\n0,1\n1\n4 0 1 2 3\n0,0\n1,0\n1,1\n0,1\n").unwrap_err().contains("outside the unit square"));
+	}
+
+	#[test]
+	fn test_choose_time() {
+		let mut taken = BTreeSet::new();
+		assert_eq!(Some(FIRST_SLOT), next_slot(0, &taken));
+		assert_eq!(Some(FIRST_SLOT + 3600), next_slot(FIRST_SLOT, &taken));
+		assert_eq!(Some(FIRST_SLOT + 3600), next_slot(FIRST_SLOT + 1, &taken));
+		taken.insert(FIRST_SLOT + 3600);
+		assert_eq!(Ok(FIRST_SLOT + 7200), choose_time(None, FIRST_SLOT + 1, &taken));
+		assert_eq!(None, next_slot(LAST_SLOT, &taken));
+
+		assert_eq!(Ok(LAST_SLOT), choose_time(Some(LAST_SLOT), 0, &taken));
+		assert!(choose_time(Some(FIRST_SLOT + 3600), 0, &taken).is_err());
+		assert!(choose_time(Some(FIRST_SLOT + 1), 0, &taken).is_err());
+		assert!(choose_time(Some(LAST_SLOT + 3600), 0, &taken).is_err());
+		assert!(choose_time(Some(FIRST_SLOT), FIRST_SLOT, &taken).is_err());
+	}
+
+	#[test]
+	fn test_publication_json() {
+		let record = Publication{problem_id: 3, publish_time: LAST_SLOT, solution_hash: "ab".to_string(), solution_size: 60,
+			problem_hash: "cd".to_string(), problem_size: 50, timestamp: 7};
+		assert_eq!(Some(record.clone()), Publication::from_json(&Json::from_str(&record.to_json().to_string()).unwrap()));
+		let publications = Publications{records: vec![record]};
+		assert_eq!(Some(3), publications.find("ab").map(|r| r.problem_id));
+		assert!(publications.slots().contains(&LAST_SLOT));
+	}
+}
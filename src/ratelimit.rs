@@ -0,0 +1,123 @@
+// Keeps us inside the contest's request quota: at most one request per
+// interval, and a token bucket holding an hour's worth of requests that
+// refills steadily. Clones share the bucket, so every API call in the process
+// draws from the same quota.
+use std::cmp;
+use std::sync::{Arc,Mutex};
+use std::thread;
+use std::time::{Duration,Instant};
+
+// longest we'll wait between retries
+const MAX_BACKOFF_SECS: u64 = 120;
+
+#[derive(Debug)]
+struct Bucket {
+	tokens: f64,
+	refilled: Instant,
+	last: Option<Instant>,
+}
+
+#[derive(Debug,Clone)]
+pub struct RateLimiter {
+	pub interval: Duration,
+	// 0 for no hourly cap, just the interval
+	pub per_hour: usize,
+	bucket: Arc<Mutex<Bucket>>,
+}
+
+fn secs(d: Duration) -> f64 {
+	d.as_secs() as f64 + d.subsec_nanos() as f64 / 1e9
+}
+
+fn duration(secs: f64) -> Duration {
+	Duration::new(secs.trunc() as u64, (secs.fract() * 1e9) as u32)
+}
+
+impl RateLimiter {
+	pub fn new(interval: Duration, per_hour: usize) -> RateLimiter {
+		let bucket = Bucket{tokens: per_hour as f64, refilled: Instant::now(), last: None};
+		RateLimiter{interval: interval, per_hour: per_hour, bucket: Arc::new(Mutex::new(bucket))}
+	}
+
+	// Takes a request's worth of quota at `now` if there is one, otherwise
+	// says how long until there will be
+	pub fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+		let mut bucket = self.bucket.lock().unwrap();
+		let rate = self.per_hour as f64 / 3600.0;
+		if now > bucket.refilled {
+			let refill = secs(now - bucket.refilled) * rate;
+			bucket.tokens = (bucket.tokens + refill).min(self.per_hour as f64);
+			bucket.refilled = now;
+		}
+		let spacing = match bucket.last {
+			Some(last) if now < last + self.interval => last + self.interval - now,
+			_ => Duration::new(0, 0)
+		};
+		let refilling = if bucket.tokens >= 1.0 || self.per_hour == 0 {
+			Duration::new(0, 0)
+		} else {
+			duration((1.0 - bucket.tokens) / rate)
+		};
+		let wait = cmp::max(spacing, refilling);
+		if wait > Duration::new(0, 0) {
+			return Err(wait);
+		}
+		if self.per_hour > 0 {
+			bucket.tokens -= 1.0;
+		}
+		bucket.last = Some(now);
+		Ok(())
+	}
+
+	// Blocks until a request may go
+	pub fn acquire(&self) {
+		while let Err(wait) = self.try_acquire_at(Instant::now()) {
+			thread::sleep(wait);
+		}
+	}
+}
+
+// How long to wait before retry number `attempt` (from 0), doubling each time
+pub fn backoff(base: Duration, attempt: u32) -> Duration {
+	let max = Duration::from_secs(MAX_BACKOFF_SECS);
+	match base.checked_mul(1 << cmp::min(attempt, 16)) {
+		Some(wait) if wait < max => wait,
+		_ => max
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration,Instant};
+
+	#[test]
+	fn test_rate_limiter() {
+		let limiter = RateLimiter::new(Duration::from_secs(1), 3);
+		let start = Instant::now();
+		assert_eq!(Ok(()), limiter.try_acquire_at(start));
+		// too soon after the last one
+		assert_eq!(Err(Duration::from_millis(500)), limiter.try_acquire_at(start + Duration::from_millis(500)));
+		assert_eq!(Ok(()), limiter.try_acquire_at(start + Duration::from_secs(1)));
+		// clones draw from the same bucket
+		let other = limiter.clone();
+		assert_eq!(Ok(()), other.try_acquire_at(start + Duration::from_secs(2)));
+		// that's the hour's three used up, and a new one takes 20 minutes to come
+		match limiter.try_acquire_at(start + Duration::from_secs(3)) {
+			Err(wait) => assert!(wait > Duration::from_secs(1190) && wait <= Duration::from_secs(1200)),
+			Ok(()) => panic!("went over the quota")
+		}
+		assert_eq!(Ok(()), limiter.try_acquire_at(start + Duration::from_secs(1210)));
+
+		// no hourly cap leaves only the interval
+		let uncapped = RateLimiter::new(Duration::from_secs(1), 0);
+		for i in 0..5000 {
+			assert_eq!(Ok(()), uncapped.try_acquire_at(start + Duration::from_secs(i)));
+		}
+		assert_eq!(Err(Duration::from_millis(500)), uncapped.try_acquire_at(start + Duration::from_millis(4999500)));
+
+		assert_eq!(Duration::from_secs(1), backoff(Duration::from_secs(1), 0));
+		assert_eq!(Duration::from_secs(8), backoff(Duration::from_secs(1), 3));
+		assert_eq!(Duration::from_secs(120), backoff(Duration::from_secs(1), 40));
+	}
+}
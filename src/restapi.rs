@@ -1,102 +1,169 @@
-use ::BASEPATH;
 use std;
-use std::io::Read;
+use std::io;
+use std::io::Write;
 use std::path::Path;
-use std::time::Duration;
 use std::thread;
 use rustc_serialize::json::Json;
-use std::process::Command;
 
-pub fn submit(problem_id: i64) {
-	// submit a solution
-	// easily submit all uncommitted solutions using e.g: 
-	// # git -C icfp2016problems status | grep solution.txt | grep -E -o "[0-9]+" | xargs -n1 cargo run submit
-	let path = format!("{}/{:05}.solution.txt", BASEPATH, problem_id);
-    println!("{}",path);
-	let path_arg = path.clone();
-	let output = Path::new(&path);
-	if output.exists() {
-        println!("Uploading solution {}", path);
-		println!("{:?}", 
-			Command::new("curl").arg("--compressed").arg("-Ss").arg("-L").arg("-H").arg("Expect:").arg("-H")
-			.arg(format!("X-API-Key: 60-d7840e0fce3dc9e9a4e2693153ccd9bc")).arg("-F").arg(format!("problem_id={}", problem_id))
-			.arg("-F").arg(format!("solution_spec=@{}", path_arg))
-			.arg("http://2016sv.icfpcontest.org/api/solution/submit")
-			.output()
-			.expect("uh-oh")
-		);
-		thread::sleep(Duration::from_millis(1000));
-	}
-}
+use blobcache;
+use blobcache::BlobCache;
+use config::Config;
+use http::{Client,HttpError,Part,Response};
+use models;
+use models::{Contest,DecodeError,ProblemMeta,SnapshotList,SubmitResponse};
+use ratelimit::{RateLimiter,backoff};
+
+// tries at each request before giving up on it
+const MAX_ATTEMPTS: u32 = 6;
 
-pub fn submit_problem(problem_id: i64) {
-	// submit a problem
-	let path = format!("{}/custom/{:05}.solution.txt", BASEPATH, problem_id);
-    println!("Taking problem from path: {}",path);
-	let path_arg = path.clone();
-	let output = Path::new(&path);
-	if output.exists() {
-        println!("Uploading solution {}", path);
-		println!("{:?}", 
-			Command::new("curl").arg("--compressed").arg("-Ss").arg("-L").arg("-H").arg("Expect:").arg("-H")
-			.arg(format!("X-API-Key: 60-d7840e0fce3dc9e9a4e2693153ccd9bc")).arg("-F").arg(format!("problem_id={}", problem_id))
-			.arg("-F").arg(format!("solution_spec=@{}", path_arg))
-      .arg("-F").arg("publish_time=1470603600")
-			.arg("http://2016sv.icfpcontest.org/api/problem/submit")
-			.output()
-			.expect("uh-oh")
-		);
-		thread::sleep(Duration::from_millis(1000));
-  } else {
-    println!("File doesn't exist");
-  }
+// Everything we say to the contest server goes through here, so it all comes
+// out of the one quota
+pub struct Api<'a> {
+	config: &'a Config,
+	client: Client,
+	limiter: RateLimiter,
+	cache: BlobCache,
 }
 
-fn download(filename: &str, apipathname: &str) {
-	// Get latest snapshot
-	let path = format!("{}/{}", BASEPATH, filename);
-	let path_arg = path.clone();
-	let output = Path::new(&path);
-	if !output.exists() {
-        println!("Saving to {}", path);
-		println!("{:?}", 
-			Command::new("curl").arg("--compressed").arg("-Ss").arg("-L").arg("-H").arg("Expect:").arg("-H")
-			.arg(format!("X-API-Key: 60-d7840e0fce3dc9e9a4e2693153ccd9bc"))
-			.arg(format!("http://2016sv.icfpcontest.org/api/{}", &apipathname))
-			.arg("-o").arg(path_arg)
-			.output()
-			.expect("uh-oh")
-		);
-		thread::sleep(Duration::from_millis(1000));
+impl From<DecodeError> for HttpError {
+	fn from(e: DecodeError) -> HttpError {
+		HttpError::BadResponse(e.0)
 	}
 }
 
-pub fn get_contest_meta() -> Vec<Json> {
-	download("contest_list.json", "snapshot/list");
-	let mut file = std::fs::File::open(format!("{}/contest_list.json", BASEPATH)).unwrap();
-	let mut data = String::new();
-	file.read_to_string(&mut data).unwrap();
-	let json = Json::from_str(&data).unwrap();
-	let contest_blobs = json.find_path(&["snapshots"]).unwrap().as_array().unwrap();
-	let latest_snapshot = contest_blobs[contest_blobs.len()-1].find_path(&["snapshot_hash"]).unwrap().as_string().unwrap();
-	let snapshot_blob = format!("blob/{}", latest_snapshot);
-	// grab latest snapshot
-	download("contest.json", &snapshot_blob);
-	file = std::fs::File::open(format!("{}/contest.json", BASEPATH)).unwrap();
-	data = String::new();
-	file.read_to_string(&mut data).unwrap();
-	let json = Json::from_str(&data).unwrap();
-	return json.find_path(&["problems"]).unwrap().as_array().unwrap().clone();
+// The server sometimes answers a rate limited submission with an ordinary
+// reply saying so, which is no different to a 429
+fn rate_limited(reply: &Json) -> bool {
+	reply.find("error").and_then(|e| e.as_string()).map_or(false, |e| e.starts_with("Rate limit"))
 }
 
-pub fn save_problems(problems: Vec<Json>) -> Vec<Json> {
-	for problem in &problems {
-		let hash = problem.find_path(&["problem_spec_hash"]).unwrap().as_string().unwrap();
-		let id = problem.find_path(&["problem_id"]).unwrap().as_i64().unwrap();
-		let blob = format!("blob/{}", hash);
-		let filename = format!("{:05}.problem.txt", id);
+impl<'a> Api<'a> {
+	pub fn new(config: &'a Config) -> Api<'a> {
+		Api{
+			config: config,
+			client: Client::new(&config.base_url, &config.api_key),
+			limiter: RateLimiter::new(config.request_interval, config.requests_per_hour),
+			cache: BlobCache::new(&config.data_dir),
+		}
+	}
+
+	// Makes the request when the quota allows, trying again with growing
+	// pauses while the failures look temporary. Requests that change something
+	// on the server are only sent again when it turned them away, lest eg. a
+	// solution go in twice.
+	fn call<F: Fn(&Client) -> Result<Response, HttpError>>(&self, what: &str, idempotent: bool, request: F) -> Result<Response, HttpError> {
+		let mut attempt = 0;
+		loop {
+			self.limiter.acquire();
+			let result = request(&self.client).and_then(|response| {
+				match response.json() {
+					Ok(ref reply) if rate_limited(reply) => Err(HttpError::Status(429, response.text())),
+					_ => Ok(response)
+				}
+			});
+			match result {
+				Err(ref e) if e.transient() && (idempotent || e.refused()) && attempt + 1 < MAX_ATTEMPTS => {
+					let wait = backoff(self.config.request_interval, attempt);
+					println!("{} failed ({}), trying again in {:.1}s", what, e, wait.as_secs() as f64 + wait.subsec_nanos() as f64 / 1e9);
+					thread::sleep(wait);
+					attempt += 1;
+				},
+				Err(e) => {
+					println!("{} failed for good after {} attempts: {}", what, attempt + 1, e);
+					return Err(e);
+				},
+				Ok(response) => return Ok(response)
+			}
+		}
+	}
+
+	// Uploads the spec at path along with the given fields as a multipart
+	// form, returning the server's reply. The reply is kept next to the spec,
+	// as eg. 00004.submission.
+	fn upload(&self, api: &str, path: &str, fields: Vec<Part>) -> Result<SubmitResponse, HttpError> {
+		println!("Uploading solution {}", path);
+		let filename = Path::new(path).file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or(path.to_string());
+		let mut parts = fields;
+		parts.push(Part::File("solution_spec".to_string(), filename, try!(blobcache::read(path))));
+		let what = format!("Uploading {}", path);
+		let response = try!(self.call(&what, false, |client| client.post_form(api, &parts)));
+		let submission = format!("{}.submission", path.trim_end_matches(".solution.txt"));
+		if let Err(e) = std::fs::File::create(&submission).and_then(|mut f| f.write_all(&response.body)) {
+			println!("Couldn't save {}: {}", submission, e);
+		}
+		Ok(try!(models::decode(&response.text())))
+	}
+
+	pub fn submit(&self, problem_id: i64) -> Result<SubmitResponse, HttpError> {
+		// submit a solution
+		// to submit only what could improve our score, use
+		// # cargo run submit --if-better
+		let path = format!("{}/{:05}.solution.txt", self.config.data_dir, problem_id);
+		self.upload("solution/submit", &path, vec![Part::Text("problem_id".to_string(), problem_id.to_string())])
+	}
+
+	// Sets the solution at path as a problem, going public at publish_time.
+	// To check it and pick the time first, use
+	// # cargo run publish <path>
+	pub fn submit_problem(&self, path: &str, publish_time: i64) -> Result<SubmitResponse, HttpError> {
+		self.upload("problem/submit", path, vec![Part::Text("publish_time".to_string(), publish_time.to_string())])
+	}
+
+	// A blob from the cache, or from the server if we don't have a good copy.
+	// A download that doesn't match its hash is tried again like any other
+	// failed request.
+	pub fn blob(&self, hash: &str) -> Result<Vec<u8>, HttpError> {
+		if let Some(data) = try!(self.cache.get(hash)) {
+			return Ok(data);
+		}
+		let path = format!("blob/{}", hash);
+		let response = try!(self.call(&format!("Downloading {}", path), true, |client| client.get(&path).and_then(|response| {
+			if blobcache::hash(&response.body) == hash {
+				Ok(response)
+			} else {
+				Err(HttpError::Io(io::Error::new(io::ErrorKind::InvalidData, format!("blob {} arrived damaged", hash))))
+			}
+		})));
+		try!(self.cache.put(hash, &response.body));
+		Ok(response.body)
+	}
+
+	// Always asks, since the list grows as the contest goes on. The last
+	// answer is kept in contest_list.json for reference.
+	pub fn snapshots(&self) -> Result<SnapshotList, HttpError> {
+		let response = try!(self.call("Listing snapshots", true, |client| client.get("snapshot/list")));
+		let path = format!("{}/contest_list.json", self.config.data_dir);
+		try!(try!(std::fs::File::create(&path)).write_all(&response.body));
+		Ok(try!(models::decode(&response.text())))
+	}
+
+	pub fn contest(&self, snapshot_hash: &str) -> Result<Contest, HttpError> {
+		let snapshot = try!(self.blob(snapshot_hash));
+		Ok(try!(models::decode(&String::from_utf8_lossy(&snapshot))))
+	}
+
+	pub fn get_contest_meta(&self) -> Result<Contest, HttpError> {
+		let list = try!(self.snapshots());
+		let latest = try!(list.latest().ok_or(HttpError::BadResponse("no snapshots yet".to_string())));
+		self.contest(&latest.snapshot_hash)
+	}
 
-		download(&filename, &blob);
+	// Makes sure each problem file is there and matches its hash, fetching
+	// those that don't. Returns the problems we couldn't get.
+	pub fn save_problems(&self, problems: &[ProblemMeta]) -> Vec<i64> {
+		let mut failed = Vec::new();
+		for problem in problems {
+			let path = format!("{}/{:05}.problem.txt", self.config.data_dir, problem.problem_id);
+			if blobcache::verify(&path, &problem.problem_spec_hash) {
+				continue;
+			}
+			println!("Saving to {}", path);
+			let saved = self.blob(&problem.problem_spec_hash).and_then(|_| self.cache.link(&problem.problem_spec_hash, &path).map_err(HttpError::from));
+			if let Err(e) = saved {
+				println!("Couldn't save problem {}: {}", problem.problem_id, e);
+				failed.push(problem.problem_id);
+			}
+		}
+		failed
 	}
-	return problems
 }
@@ -0,0 +1,268 @@
+// Everything we know about our attempts at each problem, one JSON record per
+// line in results.jsonl in the data directory. Records are only ever appended, and
+// the queries read the whole lot back.
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{SystemTime,UNIX_EPOCH};
+
+use rustc_serialize::json::{Json,ToJson};
+
+use blobcache;
+use core;
+use jsonl;
+
+pub fn results_path(data_dir: &str) -> String {
+	format!("{}/results.jsonl", data_dir)
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Record {
+	pub problem_id: i64,
+	// strategy that produced the solution
+	pub strategy: String,
+	// resemblance by our own reckoning and by the server's
+	pub resemblance: Option<f64>,
+	pub server_resemblance: Option<f64>,
+	// size as the judges count it
+	pub size: usize,
+	// sha1 of the solution, the same as the server's solution_spec_hash
+	pub hash: String,
+	// seconds since the epoch, or 0 if we don't know
+	pub timestamp: u64,
+	// why the server turned the solution down
+	pub error: Option<String>,
+	// how a batch attempt turned out, eg. "unchanged"
+	pub outcome: Option<String>,
+	// whether the solution was kept. Batch attempts no better than the one we
+	// have are recorded but not kept, everything else is.
+	pub saved: bool,
+}
+
+pub fn spec_hash(spec: &str) -> String {
+	blobcache::hash(spec.as_bytes())
+}
+
+pub fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Record {
+	pub fn new(problem_id: i64, strategy: &str, spec: &str, resemblance: Option<f64>) -> Record {
+		Record{
+			problem_id: problem_id,
+			strategy: strategy.to_string(),
+			resemblance: resemblance,
+			server_resemblance: None,
+			size: core::spec_size(spec),
+			hash: spec_hash(spec),
+			timestamp: now(),
+			error: None,
+			outcome: None,
+			saved: true,
+		}
+	}
+
+	// The server's resemblance if we've heard it, otherwise ours. A solution
+	// the server turned down scores nothing, whatever we made of it.
+	pub fn score(&self) -> Option<f64> {
+		if self.error.is_some() {
+			return None;
+		}
+		self.server_resemblance.or(self.resemblance)
+	}
+
+	pub fn from_json(json: &Json) -> Option<Record> {
+		let f64_field = |name: &str| json.find(name).and_then(|v| v.as_f64());
+		Some(Record{
+			problem_id: match json.find("problem_id").and_then(|v| v.as_i64()) { Some(id) => id, None => return None },
+			strategy: json.find("strategy").and_then(|v| v.as_string()).unwrap_or("").to_string(),
+			resemblance: f64_field("resemblance"),
+			server_resemblance: f64_field("server_resemblance"),
+			size: json.find("size").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+			hash: json.find("hash").and_then(|v| v.as_string()).unwrap_or("").to_string(),
+			timestamp: json.find("timestamp").and_then(|v| v.as_u64()).unwrap_or(0),
+			error: json.find("error").and_then(|v| v.as_string()).map(|e| e.to_string()),
+			outcome: json.find("outcome").and_then(|v| v.as_string()).map(|o| o.to_string()),
+			saved: json.find("saved").and_then(|v| v.as_boolean()).unwrap_or(true),
+		})
+	}
+}
+
+impl ToJson for Record {
+	fn to_json(&self) -> Json {
+		let mut obj = BTreeMap::new();
+		obj.insert("problem_id".to_string(), self.problem_id.to_json());
+		obj.insert("strategy".to_string(), self.strategy.to_json());
+		obj.insert("resemblance".to_string(), self.resemblance.to_json());
+		obj.insert("server_resemblance".to_string(), self.server_resemblance.to_json());
+		obj.insert("size".to_string(), self.size.to_json());
+		obj.insert("hash".to_string(), self.hash.to_json());
+		obj.insert("timestamp".to_string(), self.timestamp.to_json());
+		obj.insert("error".to_string(), self.error.to_json());
+		obj.insert("outcome".to_string(), self.outcome.to_json());
+		obj.insert("saved".to_string(), self.saved.to_json());
+		Json::Object(obj)
+	}
+}
+
+// Orders records for the same problem, best first: the higher score, then the
+// smaller solution
+fn better(a: &Record, b: &Record) -> bool {
+	match (a.score(), b.score()) {
+		(Some(x), Some(y)) if x != y => x > y,
+		(Some(_), None) => true,
+		(None, Some(_)) => false,
+		_ => a.size < b.size
+	}
+}
+
+#[derive(Debug,Clone)]
+pub struct Results {
+	// in the order they were recorded
+	pub records: Vec<Record>,
+}
+
+impl Results {
+	// Reads the store back, skipping any lines that don't make sense
+	pub fn load(path: &str) -> io::Result<Results> {
+		Ok(Results{records: try!(jsonl::load(path, "result", Record::from_json))})
+	}
+
+	pub fn append(path: &str, record: &Record) -> io::Result<()> {
+		jsonl::append(path, record)
+	}
+
+	// Whether we already have the same verdict on the same solution. Scores
+	// don't always survive the trip through JSON exactly, so they're left out.
+	pub fn known(&self, record: &Record) -> bool {
+		self.records.iter().any(|r| r.problem_id == record.problem_id && r.strategy == record.strategy &&
+			r.hash == record.hash && r.size == record.size && r.error == record.error)
+	}
+
+	// Best solution we kept for each problem
+	pub fn best(&self) -> BTreeMap<i64, &Record> {
+		let mut best: BTreeMap<i64, &Record> = BTreeMap::new();
+		for record in self.records.iter().filter(|r| r.saved) {
+			let replace = match best.get(&record.problem_id) {
+				Some(current) => better(record, current),
+				None => true
+			};
+			if replace {
+				best.insert(record.problem_id, record);
+			}
+		}
+		best
+	}
+
+	// The server's best verdict on the problem
+	pub fn best_server(&self, problem_id: i64) -> Option<&Record> {
+		self.records.iter().filter(|r| r.problem_id == problem_id && r.server_resemblance.is_some())
+			.fold(None, |best, r| match best {
+				Some(b) if !better(r, b) => Some(b),
+				_ => Some(r)
+			})
+	}
+
+	// What the server said about this exact solution, if it's seen it
+	pub fn verdict(&self, problem_id: i64, hash: &str) -> Option<&Record> {
+		self.records.iter().rev().find(|r| r.problem_id == problem_id && r.hash == hash && (r.server_resemblance.is_some() || r.error.is_some()))
+	}
+
+	// Problems we've tried but never solved perfectly
+	pub fn imperfect(&self) -> Vec<&Record> {
+		self.best().into_iter().map(|(_, r)| r).filter(|r| r.score() != Some(1.0)).collect()
+	}
+
+	// Problems whose latest attempt scored worse than their best, as pairs of
+	// the best and the latest. The latest may not have been kept, in which
+	// case the best is still in place.
+	pub fn regressions(&self) -> Vec<(&Record, &Record)> {
+		let mut latest: BTreeMap<i64, &Record> = BTreeMap::new();
+		for record in self.records.iter() {
+			latest.insert(record.problem_id, record);
+		}
+		let best = self.best();
+		latest.into_iter().filter_map(|(id, last)| {
+			let top = match best.get(&id) {
+				Some(top) => *top,
+				None => return None
+			};
+			match (top.score(), last.score()) {
+				(Some(x), Some(y)) if y < x => Some((top, last)),
+				(Some(_), None) => Some((top, last)),
+				_ => None
+			}
+		}).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record(id: i64, resemblance: f64, size: usize) -> Record {
+		Record{problem_id: id, strategy: "test".to_string(), resemblance: Some(resemblance), server_resemblance: None,
+			size: size, hash: String::new(), timestamp: 0, error: None, outcome: None, saved: true}
+	}
+
+	#[test]
+	fn test_record_json() {
+		let mut r = Record::new(4, "hull", "4\n0,0\n1,0\n1,1\n0,1\n", Some(0.5));
+		r.server_resemblance = Some(0.25);
+		r.error = Some("Facet #0 is \"wrong\"".to_string());
+		r.outcome = Some("unchanged".to_string());
+		r.saved = false;
+		assert_eq!(13, r.size);
+		assert_eq!(40, r.hash.len());
+		let line = r.to_json().to_string();
+		assert_eq!(Some(r), Json::from_str(&line).ok().and_then(|json| Record::from_json(&json)));
+		assert_eq!(None, Json::from_str("{\"strategy\":\"hull\"}").ok().and_then(|json| Record::from_json(&json)));
+	}
+
+	#[test]
+	fn test_queries() {
+		let mut perfect = record(2, 0.9, 10);
+		perfect.server_resemblance = Some(1.0);
+		let results = Results{records: vec![
+			record(1, 0.5, 10), record(1, 0.5, 8), record(1, 0.25, 5),
+			perfect, record(2, 0.95, 5),
+			record(3, 1.0, 30)
+		]};
+		let mut rejected = record(3, 1.0, 20);
+		rejected.error = Some("Invalid solution spec".to_string());
+		assert_eq!(None, rejected.score());
+		assert!(!better(&rejected, &results.records[5]));
+		assert!(results.known(&record(3, 0.5, 30)));
+		assert!(!results.known(&record(3, 1.0, 31)));
+
+		let best = results.best();
+		assert_eq!(3, best.len());
+		assert_eq!(8, best[&1].size);
+		// the server has the last word
+		assert_eq!(10, best[&2].size);
+
+		let imperfect: Vec<i64> = results.imperfect().iter().map(|r| r.problem_id).collect();
+		assert_eq!(vec![1], imperfect);
+
+		let regressions = results.regressions();
+		assert_eq!(2, regressions.len());
+		assert_eq!((8, 5), (regressions[0].0.size, regressions[0].1.size));
+		assert_eq!(2, regressions[1].1.problem_id);
+
+		// an attempt that wasn't kept still shows as a regression, but
+		// doesn't count as the best even when it scores higher
+		let mut results = results;
+		let mut unsaved = record(3, 1.0, 10);
+		unsaved.outcome = Some("unchanged".to_string());
+		unsaved.saved = false;
+		let mut worse = record(3, 0.5, 10);
+		worse.saved = false;
+		results.records.push(unsaved);
+		assert_eq!(30, results.best()[&3].size);
+		results.records.push(worse);
+		let regressions = results.regressions();
+		assert_eq!(3, regressions.len());
+		assert_eq!(30, regressions[2].0.size);
+		assert!(!regressions[2].1.saved);
+	}
+}
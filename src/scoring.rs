@@ -0,0 +1,161 @@
+// The contest's scoring, worked out from a snapshot and our results. A
+// problem is worth its setter's solution size S. With X perfect solvers,
+// each of them gets S/(X+1), the imperfect solvers share another S/(X+1) in
+// proportion to their resemblance, and the setter gets (5000-S)/(X+1).
+use std::cmp::Ordering;
+
+use core::MAX_SOLUTION_SIZE;
+use models::{Contest,Ranking};
+use results::Results;
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct ProblemScore {
+	pub problem_id: i64,
+	// the setter's solution size
+	pub value: f64,
+	// other teams with a perfect solution
+	pub perfect_solvers: usize,
+	// our best resemblance by the server's reckoning
+	pub ours: Option<f64>,
+	// what we get now, and what we'd get solving it perfectly
+	pub score: f64,
+	pub perfect_score: f64,
+}
+
+impl ProblemScore {
+	pub fn gain(&self) -> f64 {
+		self.perfect_score - self.score
+	}
+}
+
+// Points for resemblance `ours` on a problem worth `value`, given the other
+// teams' best resemblances
+pub fn problem_score(value: f64, others: &[f64], ours: Option<f64>) -> f64 {
+	let ours = match ours {
+		Some(r) if r > 0.0 => r,
+		_ => return 0.0
+	};
+	let perfect = others.iter().filter(|&&r| r == 1.0).count() + if ours == 1.0 { 1 } else { 0 };
+	let share = value / (perfect + 1) as f64;
+	if ours == 1.0 {
+		return share;
+	}
+	let total = others.iter().filter(|&&r| r < 1.0).fold(ours, |sum, r| sum + r);
+	share * ours / total
+}
+
+// Points for setting a problem worth `value` that `perfect` teams solved
+pub fn setter_score(value: f64, perfect: usize) -> f64 {
+	(MAX_SOLUTION_SIZE as f64 - value).max(0.0) / (perfect + 1) as f64
+}
+
+// The other teams' resemblances. The ranking doesn't say who's who, so
+// we're taken to be an entry matching our own best.
+fn others(ranking: &[Ranking], ours: Option<f64>) -> Vec<f64> {
+	let mut others: Vec<f64> = ranking.iter().map(|r| r.resemblance).collect();
+	if let Some(ours) = ours {
+		if let Some(i) = others.iter().position(|&r| (r - ours).abs() < 1e-9) {
+			others.remove(i);
+		}
+	}
+	others
+}
+
+#[derive(Debug,Clone)]
+pub struct Scoreboard {
+	// problems we could solve, ie. not our own
+	pub problems: Vec<ProblemScore>,
+	// what our own problems earn us
+	pub setter: f64,
+}
+
+impl Scoreboard {
+	pub fn new(contest: &Contest, results: &Results, username: &str) -> Scoreboard {
+		let mut board = Scoreboard{problems: Vec::new(), setter: 0.0};
+		for problem in contest.problems.iter() {
+			let value = problem.solution_size as f64;
+			if !username.is_empty() && problem.owner == username {
+				let perfect = problem.ranking.iter().filter(|r| r.resemblance == 1.0).count();
+				board.setter += setter_score(value, perfect);
+				continue;
+			}
+			let ours = results.best_server(problem.problem_id).and_then(|r| r.server_resemblance);
+			let others = others(&problem.ranking, ours);
+			board.problems.push(ProblemScore{
+				problem_id: problem.problem_id,
+				value: value,
+				perfect_solvers: others.iter().filter(|&&r| r == 1.0).count(),
+				ours: ours,
+				score: problem_score(value, &others, ours),
+				perfect_score: problem_score(value, &others, Some(1.0)),
+			});
+		}
+		board
+	}
+
+	pub fn total(&self) -> f64 {
+		self.problems.iter().fold(self.setter, |sum, p| sum + p.score)
+	}
+
+	// Problems where solving perfectly would gain us something, the most
+	// first. Not always those we haven't solved: being the only imperfect
+	// solver can be worth more than being one of many perfect ones.
+	pub fn priorities(&self) -> Vec<&ProblemScore> {
+		let mut todo: Vec<&ProblemScore> = self.problems.iter().filter(|p| p.gain() > 0.0).collect();
+		todo.sort_by(|a, b| b.gain().partial_cmp(&a.gain()).unwrap_or(Ordering::Equal));
+		todo
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use models::{Contest,ProblemMeta,Ranking};
+	use results::{Record,Results};
+
+	fn problem(id: i64, owner: &str, value: usize, ranking: Vec<f64>) -> ProblemMeta {
+		ProblemMeta{problem_id: id, publish_time: 0, owner: owner.to_string(), problem_size: 50, solution_size: value,
+			problem_spec_hash: String::new(), ranking: ranking.into_iter().map(|r| Ranking{resemblance: r, solution_size: 10}).collect()}
+	}
+
+	fn ours(id: i64, server: f64) -> Record {
+		Record{problem_id: id, strategy: "test".to_string(), resemblance: None, server_resemblance: Some(server),
+			size: 10, hash: String::new(), timestamp: 0, error: None, outcome: None, saved: true}
+	}
+
+	#[test]
+	fn test_problem_score() {
+		// alone and perfect takes half, the other half being for imperfect solvers
+		assert_eq!(50.0, problem_score(100.0, &[], Some(1.0)));
+		assert_eq!(25.0, problem_score(100.0, &[1.0, 1.0, 0.5], Some(1.0)));
+		// 0.5 of 0.5 + 0.25 + 0.25 of the imperfect share
+		assert_eq!(50.0, problem_score(100.0, &[0.25, 0.25], Some(0.5)));
+		assert_eq!(25.0, problem_score(100.0, &[1.0, 0.5], Some(0.5)));
+		// the whole imperfect share beats a third of the perfect one
+		assert!(problem_score(100.0, &[1.0, 1.0], Some(0.5)) > problem_score(100.0, &[1.0, 1.0], Some(1.0)));
+		assert_eq!(0.0, problem_score(100.0, &[1.0], None));
+		assert_eq!(2450.0, setter_score(100.0, 1));
+	}
+
+	#[test]
+	fn test_scoreboard() {
+		let contest = Contest{snapshot_time: 0, leaderboard: Vec::new(), users: Vec::new(), problems: vec![
+			problem(1, "7", 100, vec![1.0, 0.5]),
+			problem(2, "7", 300, vec![1.0, 0.5, 0.5]),
+			problem(3, "7", 60, vec![]),
+			problem(4, "42", 1000, vec![1.0]),
+		]};
+		let results = Results{records: vec![ours(1, 1.0), ours(2, 0.5)]};
+		let board = Scoreboard::new(&contest, &results, "42");
+		assert_eq!(3, board.problems.len());
+		assert_eq!(2000.0, board.setter);
+		assert_eq!(50.0, board.problems[0].score);
+		assert_eq!(1, board.problems[1].perfect_solvers);
+		assert_eq!(75.0, board.problems[1].score);
+		assert_eq!(2125.0, board.total());
+
+		let todo: Vec<i64> = board.priorities().iter().map(|p| p.problem_id).collect();
+		assert_eq!(vec![3, 2], todo);
+		assert_eq!(30.0, board.priorities()[0].gain());
+	}
+}
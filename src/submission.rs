@@ -0,0 +1,112 @@
+// Which solutions are worth uploading: only those that pass our own checks
+// and beat the best the server has seen from us, judging by the results store.
+use std::fmt;
+use std::fs;
+use std::io::Read;
+
+use core;
+use results::{Record,Results};
+
+// Our resemblance is sampled and only good to a percent or so, so a solution
+// that comes close to the server's best is worth the server's exact verdict
+const TOLERANCE: f64 = 0.01;
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum Decision {
+	// our resemblance, and the server's best so far if it has one
+	Submit(f64, Option<f64>),
+	NoSolution,
+	Invalid(String),
+	// the server has judged this exact solution already
+	AlreadySubmitted,
+	// ours, and the server's best, which is perfect or well ahead of it
+	NotBetter(f64, f64),
+}
+
+impl Decision {
+	pub fn kind(&self) -> &'static str {
+		match *self {
+			Decision::Submit(..) => "to submit",
+			Decision::NoSolution => "without a solution",
+			Decision::Invalid(_) => "invalid",
+			Decision::AlreadySubmitted => "already submitted",
+			Decision::NotBetter(..) => "no better",
+		}
+	}
+}
+
+impl fmt::Display for Decision {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Decision::Submit(ours, Some(best)) => write!(f, "submit, {} against the best of {}", ours, best),
+			Decision::Submit(ours, None) => write!(f, "submit, {} and nothing on the server yet", ours),
+			Decision::NoSolution => write!(f, "skip, no solution"),
+			Decision::Invalid(ref why) => write!(f, "skip, invalid: {}", why),
+			Decision::AlreadySubmitted => write!(f, "skip, already submitted"),
+			Decision::NotBetter(ours, best) => write!(f, "skip, {} is no better than {}", ours, best),
+		}
+	}
+}
+
+// What to do with a solution, given its hash and our resemblance for it
+pub fn decide(results: &Results, problem_id: i64, hash: &str, resemblance: f64) -> Decision {
+	if results.verdict(problem_id, hash).is_some() {
+		return Decision::AlreadySubmitted;
+	}
+	match results.best_server(problem_id).and_then(|r| r.server_resemblance) {
+		// nothing beats perfect
+		Some(best) if best >= 1.0 || resemblance + TOLERANCE < best => Decision::NotBetter(resemblance, best),
+		best => Decision::Submit(resemblance, best)
+	}
+}
+
+// Checks the solution on disk for the problem. Returns a record of it, ready
+// for the server's verdict, whenever it's one we could submit.
+pub fn consider(data_dir: &str, problem_id: i64, results: &Results) -> (Decision, Option<Record>) {
+	let mut spec = String::new();
+	let path = format!("{}/{:05}.solution.txt", data_dir, problem_id);
+	if fs::File::open(&path).and_then(|mut f| f.read_to_string(&mut spec)).is_err() {
+		return (Decision::NoSolution, None);
+	}
+	let (shape, _) = match ::load_problem(data_dir, problem_id) {
+		Ok(problem) => problem,
+		Err(e) => return (Decision::Invalid(format!("can't read the problem: {:?}", e)), None)
+	};
+	let parsed = match core::judge(&spec) {
+		Ok(parsed) => parsed,
+		Err(why) => return (Decision::Invalid(why), None)
+	};
+	let record = Record::new(problem_id, "submit", &spec, Some(core::resemblance(&shape, &parsed.dst_polys())));
+	(decide(results, problem_id, &record.hash, record.resemblance.unwrap()), Some(record))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use results::{Record,Results};
+
+	fn record(id: i64, hash: &str, server: Option<f64>, error: Option<&str>) -> Record {
+		Record{problem_id: id, strategy: "test".to_string(), resemblance: Some(0.9), server_resemblance: server,
+			size: 10, hash: hash.to_string(), timestamp: 0, error: error.map(|e| e.to_string()), outcome: None, saved: true}
+	}
+
+	#[test]
+	fn test_decide() {
+		let results = Results{records: vec![
+			record(1, "a", Some(0.5), None), record(1, "b", Some(0.75), None),
+			record(2, "c", None, Some("Invalid solution spec")),
+			// ours alone counts for nothing
+			record(3, "d", None, None),
+			record(4, "f", Some(1.0), None),
+		]};
+		assert_eq!(Decision::Submit(0.8, Some(0.75)), decide(&results, 1, "e", 0.8));
+		// too close to call on our estimate alone
+		assert_eq!(Decision::Submit(0.745, Some(0.75)), decide(&results, 1, "e", 0.745));
+		assert_eq!(Decision::NotBetter(0.7, 0.75), decide(&results, 1, "e", 0.7));
+		assert_eq!(Decision::AlreadySubmitted, decide(&results, 1, "a", 1.0));
+		assert_eq!(Decision::AlreadySubmitted, decide(&results, 2, "c", 1.0));
+		assert_eq!(Decision::Submit(0.1, None), decide(&results, 2, "e", 0.1));
+		assert_eq!(Decision::Submit(0.1, None), decide(&results, 3, "d", 0.1));
+		assert_eq!(Decision::NotBetter(1.0, 1.0), decide(&results, 4, "g", 1.0));
+	}
+}
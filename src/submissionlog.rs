@@ -0,0 +1,184 @@
+// Reads back the submissionlog kept during the contest. It's the console
+// output of `submit` runs: the path being uploaded, then the Debug form of
+// curl's process::Output with the server's JSON reply escaped inside it, mixed
+// in with whatever else cargo and the shell had to say.
+use std::io;
+use std::io::BufRead;
+
+use rustc_serialize::json::Json;
+
+use results::Record;
+
+// What the server said about one submission
+#[derive(Debug,Clone,PartialEq)]
+pub struct Entry {
+	pub problem_id: i64,
+	pub ok: bool,
+	pub resemblance: Option<f64>,
+	pub solution_size: Option<usize>,
+	pub hash: Option<String>,
+	pub error: Option<String>,
+}
+
+impl Entry {
+	// Failures that say nothing about the solution itself
+	pub fn transient(&self) -> bool {
+		match self.error {
+			Some(ref e) => e.starts_with("Rate limit") || e.starts_with("Server error"),
+			None => false
+		}
+	}
+
+	pub fn record(&self) -> Record {
+		Record{
+			problem_id: self.problem_id,
+			strategy: "submissionlog".to_string(),
+			resemblance: None,
+			server_resemblance: self.resemblance,
+			size: self.solution_size.unwrap_or(0),
+			hash: self.hash.clone().unwrap_or(String::new()),
+			// the log doesn't say when
+			timestamp: 0,
+			error: self.error.clone(),
+			outcome: None,
+			saved: true,
+		}
+	}
+}
+
+// Undoes Rust's Debug escaping of a string
+fn unescape(s: &str) -> String {
+	let mut out = String::new();
+	let mut chars = s.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			out.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('n') => out.push('\n'),
+			Some('t') => out.push('\t'),
+			Some('r') => out.push('\r'),
+			Some('0') => out.push('\0'),
+			Some('u') => {
+				// \u{XXXX}
+				let hex: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+				if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32) {
+					out.push(c);
+				}
+			},
+			Some(c) => out.push(c),
+			None => {}
+		}
+	}
+	out
+}
+
+// The stdout string out of a printed process::Output, unescaped
+fn stdout_of(line: &str) -> Option<String> {
+	const START: &'static str = "stdout: \"";
+	let start = match line.find(START) {
+		Some(i) => i + START.len(),
+		None => return None
+	};
+	let mut escaped = false;
+	for (i, c) in line[start..].char_indices() {
+		match c {
+			'\\' if !escaped => escaped = true,
+			'"' if !escaped => return Some(unescape(&line[start..start + i])),
+			_ => escaped = false
+		}
+	}
+	None
+}
+
+// The problem being submitted, from the lines leading up to the output
+fn problem_in(line: &str) -> Option<i64> {
+	let line = line.trim();
+	let path = if line.starts_with("icfp2016problems/") || line.starts_with("Uploading solution ") || line.contains("teamsuperlegit submit ") {
+		line.trim_end_matches('`')
+	} else {
+		return None;
+	};
+	let name = path.rsplit(|c| c == '/' || c == ' ').next().unwrap_or("");
+	name.trim_end_matches(".solution.txt").parse::<i64>().ok()
+}
+
+fn entry(problem: Option<i64>, stdout: &str) -> Option<Entry> {
+	let json = match Json::from_str(stdout) {
+		Ok(json) => json,
+		Err(_) => {
+			// an error page from the server rather than an answer
+			let title = stdout.split("<title>").nth(1).and_then(|t| t.split("</title>").next()).unwrap_or("no reply");
+			return problem.map(|id| Entry{
+				problem_id: id, ok: false, resemblance: None, solution_size: None, hash: None,
+				error: Some(format!("Server error: {}", title))
+			});
+		}
+	};
+	let id = match json.find("problem_id").and_then(|v| v.as_i64()).or(problem) {
+		Some(id) => id,
+		None => return None
+	};
+	Some(Entry{
+		problem_id: id,
+		ok: json.find("ok").and_then(|v| v.as_boolean()).unwrap_or(false),
+		resemblance: json.find("resemblance").and_then(|v| v.as_f64()),
+		solution_size: json.find("solution_size").and_then(|v| v.as_u64()).map(|s| s as usize),
+		hash: json.find("solution_spec_hash").and_then(|v| v.as_string()).map(|h| h.to_string()),
+		error: json.find("error").and_then(|v| v.as_string()).map(|e| e.to_string()),
+	})
+}
+
+// Every submission in the log, in order. Also returns how many replies
+// couldn't be matched up with a problem.
+pub fn parse_log<R: BufRead>(log: R) -> io::Result<(Vec<Entry>, usize)> {
+	let mut entries = Vec::new();
+	let mut unmatched = 0;
+	let mut problem = None;
+	for line in log.lines() {
+		let line = try!(line);
+		if line.starts_with("Output {") {
+			match stdout_of(&line).and_then(|stdout| entry(problem, &stdout)) {
+				Some(e) => entries.push(e),
+				None => unmatched += 1
+			}
+			problem = None;
+		} else if let Some(id) = problem_in(&line) {
+			problem = Some(id);
+		}
+	}
+	Ok((entries, unmatched))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_log() {
+		let log = r#"Running "submit"
+icfp2016problems/00004.solution.txt
+Uploading solution icfp2016problems/00004.solution.txt
+Output { status: ExitStatus(ExitStatus(0)), stdout: "{\"solution_size\":60,\"ok\":true,\"resemblance\":0.5,\"problem_id\":4,\"solution_spec_hash\":\"feb74e7c\"}", stderr: "" }
+fatal: pathspec 'icfp2016problems/2016.solution.txt' did not match any files
+     Running `target/debug/teamsuperlegit submit 01274`
+Output { status: ExitStatus(ExitStatus(0)), stdout: "{\"ok\":false,\"error\":\"Invalid solution spec: Current coverage area = 1\\/2\"}", stderr: "" }
+Output { status: ExitStatus(ExitStatus(0)), stdout: "{\"ok\":false,\"error\":\"Rate limit exceeded\"}", stderr: "" }
+Uploading solution icfp2016problems/01325.solution.txt
+Output { status: ExitStatus(ExitStatus(0)), stdout: "<!DOCTYPE html>\n<title>Error 502 (Server Error)!!1</title>\n<ins>That\u{2019}s an error.</ins>\n", stderr: "" }
+"#;
+		let (entries, unmatched) = parse_log(log.as_bytes()).unwrap();
+		// the rate limit doesn't say which problem it was
+		assert_eq!(1, unmatched);
+		assert_eq!(3, entries.len());
+		assert_eq!(Entry{problem_id: 4, ok: true, resemblance: Some(0.5), solution_size: Some(60), hash: Some("feb74e7c".to_string()), error: None}, entries[0]);
+		assert_eq!(1274, entries[1].problem_id);
+		assert!(!entries[1].ok);
+		assert_eq!(Some("Invalid solution spec: Current coverage area = 1/2".to_string()), entries[1].error);
+		assert!(!entries[1].transient());
+		assert_eq!(1325, entries[2].problem_id);
+		assert_eq!(Some("Server error: Error 502 (Server Error)!!1".to_string()), entries[2].error);
+		assert!(entries[2].transient());
+	}
+}
@@ -0,0 +1,203 @@
+// Bringing our copy of the contest up to date: the newest snapshot, any
+// problems we're missing, and what changed since the last time we looked.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{Read,Write};
+
+use blobcache::BlobCache;
+use http::HttpError;
+use models;
+use models::{Contest,ProblemMeta};
+use restapi::Api;
+
+// holds the hash of the snapshot we last synced with
+const SYNCED_FILE: &'static str = "synced_snapshot";
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct LeaderChange {
+	pub username: String,
+	pub display_name: String,
+	// rank, from 1, and score, before and after
+	pub before: Option<(usize, f64)>,
+	pub after: Option<(usize, f64)>,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct SnapshotDiff {
+	pub snapshot_time: i64,
+	pub new_problems: Vec<i64>,
+	pub removed_problems: Vec<i64>,
+	// what changed about each problem, eg. "solution_size 80 -> 60"
+	pub changed: Vec<(i64, Vec<String>)>,
+	pub leaderboard: Vec<LeaderChange>,
+}
+
+fn perfect(problem: &ProblemMeta) -> usize {
+	problem.ranking.iter().filter(|r| r.resemblance == 1.0).count()
+}
+
+fn changes(old: &ProblemMeta, new: &ProblemMeta) -> Vec<String> {
+	let mut changes = Vec::new();
+	if old.problem_spec_hash != new.problem_spec_hash {
+		changes.push(format!("spec {} -> {}", old.problem_spec_hash, new.problem_spec_hash));
+	}
+	if old.solution_size != new.solution_size {
+		changes.push(format!("solution_size {} -> {}", old.solution_size, new.solution_size));
+	}
+	if old.ranking.len() != new.ranking.len() {
+		changes.push(format!("solvers {} -> {}", old.ranking.len(), new.ranking.len()));
+	}
+	if perfect(old) != perfect(new) {
+		changes.push(format!("perfect solvers {} -> {}", perfect(old), perfect(new)));
+	}
+	changes
+}
+
+// Ranks and scores by username
+fn standings(contest: &Contest) -> BTreeMap<&str, (usize, f64)> {
+	contest.leaderboard.iter().enumerate().map(|(i, l)| (l.username.as_str(), (i + 1, l.score))).collect()
+}
+
+// What's different in `new`. Everything is new when there's nothing to compare with.
+pub fn diff(old: Option<&Contest>, new: &Contest) -> SnapshotDiff {
+	let before: BTreeMap<i64, &ProblemMeta> = old.map_or(Vec::new(), |c| c.problems.iter().collect()).into_iter().map(|p| (p.problem_id, p)).collect();
+	let after: BTreeMap<i64, &ProblemMeta> = new.problems.iter().map(|p| (p.problem_id, p)).collect();
+	let mut diff = SnapshotDiff{
+		snapshot_time: new.snapshot_time,
+		new_problems: after.keys().filter(|id| !before.contains_key(id)).cloned().collect(),
+		removed_problems: before.keys().filter(|id| !after.contains_key(id)).cloned().collect(),
+		changed: Vec::new(),
+		leaderboard: Vec::new(),
+	};
+	for (id, problem) in after.iter() {
+		if let Some(old) = before.get(id) {
+			let changes = changes(old, problem);
+			if !changes.is_empty() {
+				diff.changed.push((*id, changes));
+			}
+		}
+	}
+
+	let (was, now) = (old.map_or(BTreeMap::new(), standings), standings(new));
+	let names: BTreeMap<&str, &str> = new.users.iter().map(|u| (u.username.as_str(), u.display_name.as_str())).collect();
+	let mut users: Vec<&str> = was.keys().chain(now.keys()).cloned().collect();
+	users.sort();
+	users.dedup();
+	for user in users {
+		let (before, after) = (was.get(user).cloned(), now.get(user).cloned());
+		if before != after {
+			diff.leaderboard.push(LeaderChange{
+				username: user.to_string(),
+				display_name: names.get(user).unwrap_or(&user).to_string(),
+				before: before,
+				after: after,
+			});
+		}
+	}
+	// in order of where they stand now
+	diff.leaderboard.sort_by_key(|c| c.after.map_or(usize::max_value(), |a| a.0));
+	diff
+}
+
+impl fmt::Display for SnapshotDiff {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		try!(writeln!(f, "Snapshot of {}: {} new problems {:?}", self.snapshot_time, self.new_problems.len(), self.new_problems));
+		if !self.removed_problems.is_empty() {
+			try!(writeln!(f, "Gone: {:?}", self.removed_problems));
+		}
+		for &(id, ref changes) in self.changed.iter() {
+			try!(writeln!(f, "Problem {}: {}", id, changes.join(", ")));
+		}
+		for change in self.leaderboard.iter() {
+			let place = |p: Option<(usize, f64)>| p.map_or("-".to_string(), |(rank, score)| format!("#{} ({})", rank, score));
+			try!(writeln!(f, "{}: {} -> {}", change.display_name, place(change.before), place(change.after)));
+		}
+		Ok(())
+	}
+}
+
+fn read_synced(data_dir: &str) -> io::Result<Option<String>> {
+	let mut hash = String::new();
+	match fs::File::open(format!("{}/{}", data_dir, SYNCED_FILE)) {
+		Ok(mut file) => try!(file.read_to_string(&mut hash)),
+		Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(e)
+	};
+	Ok(Some(hash.trim().to_string()).filter(|h| !h.is_empty()))
+}
+
+// The contest as of the last sync, straight from the blob cache
+pub fn last_contest(data_dir: &str) -> Option<Contest> {
+	let hash = match read_synced(data_dir) {
+		Ok(Some(hash)) => hash,
+		_ => return None
+	};
+	cached_contest(data_dir, &hash)
+}
+
+// A snapshot we've downloaded before, without going back to the server
+fn cached_contest(data_dir: &str, hash: &str) -> Option<Contest> {
+	match BlobCache::new(data_dir).get(hash) {
+		Ok(Some(blob)) => models::decode(&String::from_utf8_lossy(&blob)).ok(),
+		_ => None
+	}
+}
+
+// Fetches the newest snapshot and whichever of its problems we're missing.
+// Returns what changed since the last sync, and the problems we couldn't get.
+pub fn sync(api: &Api, data_dir: &str) -> Result<(SnapshotDiff, Vec<i64>), HttpError> {
+	let list = try!(api.snapshots());
+	let latest = try!(list.latest().ok_or(HttpError::BadResponse("no snapshots yet".to_string())));
+	let contest = try!(api.contest(&latest.snapshot_hash));
+	let old = match try!(read_synced(data_dir)) {
+		// only if it's still in the blob cache, it isn't worth downloading again
+		Some(ref hash) if *hash != latest.snapshot_hash => cached_contest(data_dir, hash),
+		Some(_) => Some(contest.clone()),
+		None => None
+	};
+	let failed = api.save_problems(&contest.problems);
+	try!(try!(fs::File::create(format!("{}/{}", data_dir, SYNCED_FILE))).write_all(latest.snapshot_hash.as_bytes()));
+	Ok((diff(old.as_ref(), &contest), failed))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use models::{Contest,Leader,ProblemMeta,Ranking,User};
+
+	fn problem(id: i64, rankings: Vec<f64>) -> ProblemMeta {
+		ProblemMeta{problem_id: id, publish_time: 0, owner: "1".to_string(), problem_size: 50, solution_size: 60,
+			problem_spec_hash: format!("hash{}", id), ranking: rankings.into_iter().map(|r| Ranking{resemblance: r, solution_size: 60}).collect()}
+	}
+
+	fn contest(time: i64, problems: Vec<ProblemMeta>, leaders: Vec<(&str, f64)>) -> Contest {
+		Contest{
+			snapshot_time: time,
+			problems: problems,
+			leaderboard: leaders.iter().map(|&(u, s)| Leader{username: u.to_string(), score: s}).collect(),
+			users: vec![User{username: "42".to_string(), display_name: "Super Legit".to_string()}],
+		}
+	}
+
+	#[test]
+	fn test_diff() {
+		let old = contest(1, vec![problem(1, vec![1.0]), problem(2, vec![0.5])], vec![("7", 10.0), ("42", 5.0)]);
+		let new = contest(2, vec![problem(1, vec![1.0]), problem(2, vec![0.5, 1.0]), problem(3, vec![])], vec![("42", 12.0), ("7", 10.0), ("8", 1.0)]);
+		let d = diff(Some(&old), &new);
+		assert_eq!(vec![3], d.new_problems);
+		assert!(d.removed_problems.is_empty());
+		assert_eq!(vec![(2, vec!["solvers 1 -> 2".to_string(), "perfect solvers 0 -> 1".to_string()])], d.changed);
+		let moved: Vec<(&str, Option<usize>, Option<usize>)> = d.leaderboard.iter()
+			.map(|c| (c.display_name.as_str(), c.before.map(|b| b.0), c.after.map(|a| a.0))).collect();
+		assert_eq!(vec![("Super Legit", Some(2), Some(1)), ("7", Some(1), Some(2)), ("8", None, Some(3))], moved);
+
+		// nothing to go on, so it's all new
+		let d = diff(None, &new);
+		assert_eq!(vec![1, 2, 3], d.new_problems);
+		assert!(d.changed.is_empty());
+		assert_eq!(3, d.leaderboard.len());
+		assert!(diff(Some(&new), &new).leaderboard.is_empty());
+	}
+}
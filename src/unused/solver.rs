@@ -1,6 +0,0 @@
-use core::*;
-
-pub fn solve<N: Num>(shape: Shape<N>, skeleton: Skeleton<N>) {
-    let (_, _) = (shape, skeleton); // just to silence unused variable warnings
-    println!("Now that's a SUPER LEGIT solution");
-}
@@ -12,28 +12,23 @@ pub trait Folds<N: Num> {
 	fn transform(&self, src: &Point<N>) -> Point<N>;
 }
 
-// XXX unfinished
+// `points` are the distinct facet corners, facets index into them
 fn facets<N: Num>(skel: Skeleton<N>) -> (Vec<Point<N>>, Vec<Vec<usize>>) {
+	let mut index = BTreeMap::new();
 	let mut points = Vec::new();
-	let facets = Vec::new();
-	/* 1. find edges which share a vertex
-	** 2. sort edges according to angle
-	** 3. construct poly using shortest line segments along adjacent angles */
-	for i in 0..skel.lines.len() {
-		for j in i+1..skel.lines.len() {
-			if let Some(p) = intersect_discrete(&skel.lines[i], &skel.lines[j]) {
+	let mut facets = Vec::new();
+	for face in faces(&skel.lines) {
+		let mut facet = Vec::new();
+		for p in face.points {
+			let next = points.len();
+			let i = *index.entry(p.clone()).or_insert(next);
+			if i == next {
 				points.push(p);
 			}
+			facet.push(i);
 		}
+		facets.push(facet);
 	}
-	for line in skel.lines {
-		points.push(line.p1.clone());
-		points.push(line.p2.clone());
-	}
-	//points.sort_by(|a, b| if (a < b) { Ordering::Less } else if (a > b) { Ordering::Greater } else { Ordering::Equal }});
-	points.sort();
-	points.dedup();
-
 	(points, facets)
 }
 
@@ -50,10 +45,11 @@ pub fn from_skeleton<N: Num, F: Folds<N>, W: Write>(writer: W, skel: Skeleton<N>
 fn snap(p: Point<BigRational>) -> Point<BigRational> {
 	let mut p = p.clone();
 	let snapdist = 0.000000001;
-	for (float, snap) in vec![(0.0, Zero::zero()),(1.0, One::one())] {
-		let snap: BigRational = snap;
-		p.x = if (p.x.to_f64() - float).abs() < snapdist { snap.clone() } else { p.x };
-		p.y = if (p.y.to_f64() - float).abs() < snapdist { snap.clone() } else { p.y };
+	let snaps: Vec<BigRational> = vec![Zero::zero(), One::one()];
+	for snap in snaps {
+		// compare exact differences, floats can't tell huge coordinates apart
+		p.x = if (p.x.clone() - snap.clone()).to_f64().abs() < snapdist { snap.clone() } else { p.x };
+		p.y = if (p.y.clone() - snap.clone()).to_f64().abs() < snapdist { snap.clone() } else { p.y };
 	}
 	return p;
 }
@@ -67,7 +63,7 @@ fn qntz(p: Point<BigRational>, base: BigInt) -> Point<BigRational> {
 		x: BigRational::new(BigInt::from(xnum), BigInt::from(base.clone())),
 		y: BigRational::new(BigInt::from(ynum), BigInt::from(base.clone()))
 	};
-	if (p.x.to_f64() - p2.x.to_f64()).abs() < 0.000000001 && (p.y.to_f64() - p2.y.to_f64()).abs() < 0.000000001 {
+	if (p.x.clone() - p2.x.clone()).to_f64().abs() < 0.000000001 && (p.y.clone() - p2.y.clone()).to_f64().abs() < 0.000000001 {
 		return p2;
 	} else {
 		return p;
@@ -85,19 +81,22 @@ pub fn from_polys<W: Write>(writer: W, polys: Vec<Polygon<BigRational>>, base: B
 		//poly.source_poly().printcongruency();
 		let mut facet = Vec::new();
 		let mut orig = Vec::new();
+		let inverse = poly.transform.inverse();
 		for point in poly.points {
 			let p = find_close_rational_point(qntz(snap(point.clone()), base.clone()));
+			let s = find_close_rational_point(qntz(snap(inverse.transform(p.clone())), base.clone()));
+			// vertices are identified by their source position; several layers
+			// of paper can share the same destination
 			let i = {
-				let e = seen.entry(p.clone());
+				let e = seen.entry(s.clone());
 				match e {
 					Entry::Occupied(e) => {
 						*e.get()
 					},
 					Entry::Vacant(e) => {
-						src.push(find_close_rational_point(qntz(snap(poly.transform.inverse().transform(p.clone())), base.clone())));
+						src.push(s.clone());
 						dst.push(p.clone());
 						let i = dst.len() - 1;
-						println!("   POINT {} {} -> {}", i, src[i], dst[i]);
 						*e.insert(i)
 					}
 				}